@@ -1,12 +1,18 @@
-//! Hashing, integrity verification, and tar.gz archive creation.
+//! Hashing, integrity verification, and tar.gz archive creation and reading.
 //!
 //! This module contains pure functions — no state, no I/O.
 
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
 use anyhow::{Context, Result};
-use data_encoding::BASE64;
+use data_encoding::{BASE64, HEXLOWER};
 use md5::{Digest, Md5};
 use sha1::Sha1;
-use sha2::Sha512;
+use sha2::digest::DynDigest;
+use sha2::{Sha256, Sha384, Sha512};
+use sha3::{Sha3_256, Sha3_512};
 
 use crate::error::VerifyResult;
 
@@ -19,6 +25,66 @@ pub fn sig_md5(content: &[u8]) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// Calculate SHA-1 hash of byte content (hex-encoded), as in a lockfile
+/// `shasum`.
+pub fn sig_sha1(content: &[u8]) -> String {
+    HEXLOWER.encode(&Sha1::digest(content))
+}
+
+/// Calculate SHA-256 hash of byte content (hex-encoded).
+pub fn sig_sha256(content: &[u8]) -> String {
+    HEXLOWER.encode(&Sha256::digest(content))
+}
+
+/// Calculate SHA-512 hash of byte content (hex-encoded).
+pub fn sig_sha512(content: &[u8]) -> String {
+    HEXLOWER.encode(&Sha512::digest(content))
+}
+
+/// Calculate SHA-512 hash of byte content (base64-encoded), the digest
+/// part of a `sha512-…` integrity string.
+pub fn sig_sha512_base64(content: &[u8]) -> String {
+    BASE64.encode(&Sha512::digest(content))
+}
+
+/// Chunk size for the `*_stream` hash functions.
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Most bytes reserved up front for a tar entry. The header size is only a
+/// hint from the archive; larger entries grow the buffer as they are read.
+const MAX_ENTRY_PREALLOC: u64 = 1 << 20;
+
+/// Initial buffer capacity for a tar entry of `size` bytes (from its
+/// header), capped at [`MAX_ENTRY_PREALLOC`].
+pub(crate) fn entry_capacity(size: u64) -> usize {
+    size.min(MAX_ENTRY_PREALLOC) as usize
+}
+
+/// [`sig_md5`] of everything `reader` yields, read in 64 KiB chunks so the
+/// content never has to be in memory at once.
+pub fn sig_md5_stream<R: Read>(reader: &mut R) -> Result<String> {
+    Ok(HEXLOWER.encode(&digest_stream::<Md5, R>(reader)?))
+}
+
+/// [`sig_sha512`] of everything `reader` yields, read in 64 KiB chunks.
+pub fn sig_sha512_stream<R: Read>(reader: &mut R) -> Result<String> {
+    Ok(HEXLOWER.encode(&digest_stream::<Sha512, R>(reader)?))
+}
+
+fn digest_stream<D: Digest, R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut hasher = D::new();
+    let mut chunk = vec![0; HASH_CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&chunk[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("Failed to read content to hash"),
+        }
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
 /// Verify file integrity.
 ///
 /// Returns [`VerifyResult::Verified`] if a hash was present and matched,
@@ -26,37 +92,237 @@ pub fn sig_md5(content: &[u8]) -> String {
 /// [`VerifyResult::NoHashAvailable`] if neither `integrity` nor `shasum`
 /// was provided.
 ///
-/// `integrity` takes priority over `shasum` when both are present.
+/// `integrity` is an SRI string (`sha512-`, `sha384-`, `sha256-`,
+/// `sha3-512-` or `sha3-256-`) and takes priority over `shasum` when both
+/// are present. With several hashes (space-separated) only the strongest
+/// supported algorithm is checked; any of its digests may match.
 pub fn verify_integrity(
     file_bytes: &[u8],
     integrity: Option<&str>,
     shasum: Option<&str>,
 ) -> VerifyResult {
-    if let Some(integrity_str) = integrity
-        && let Some(hash_part) = integrity_str.strip_prefix("sha512-")
-    {
-        let mut hasher = Sha512::new();
-        hasher.update(file_bytes);
-        let calculated = BASE64.encode(&hasher.finalize());
-        return if calculated == hash_part {
-            VerifyResult::Verified
-        } else {
-            VerifyResult::Failed
-        };
+    let mut hasher = IntegrityHasher::new(integrity, shasum);
+    hasher.update(file_bytes);
+    hasher.finish()
+}
+
+/// Incremental [`verify_integrity`] for data that arrives in chunks, e.g.
+/// a streamed download.
+pub struct IntegrityHasher {
+    state: Option<(Box<dyn DynDigest>, ExpectedDigest)>,
+}
+
+enum ExpectedDigest {
+    /// Base64 digests from an SRI string, all of one algorithm
+    Sri(Vec<String>),
+    /// Hex SHA-1 `shasum`
+    Hex(String),
+}
+
+impl IntegrityHasher {
+    /// Start hashing against `integrity` (preferred) or `shasum`.
+    pub fn new(integrity: Option<&str>, shasum: Option<&str>) -> Self {
+        if let Some((algorithm, digests)) = integrity.and_then(strongest_sri)
+            && let Some(hasher) = sri_hasher(algorithm)
+        {
+            return Self {
+                state: Some((hasher, ExpectedDigest::Sri(digests))),
+            };
+        }
+        let state = shasum.map(|expected| {
+            let hasher: Box<dyn DynDigest> = Box::new(Sha1::new());
+            (hasher, ExpectedDigest::Hex(expected.to_string()))
+        });
+        Self { state }
     }
 
-    if let Some(expected) = shasum {
-        let mut hasher = Sha1::new();
-        hasher.update(file_bytes);
-        let calculated = format!("{:x}", hasher.finalize());
-        return if calculated == expected {
+    /// Feed the next chunk of data.
+    pub fn update(&mut self, chunk: &[u8]) {
+        if let Some((hasher, _)) = &mut self.state {
+            hasher.update(chunk);
+        }
+    }
+
+    /// Compare the digest of everything fed so far with the expected one.
+    pub fn finish(self) -> VerifyResult {
+        let Some((hasher, expected)) = self.state else {
+            return VerifyResult::NoHashAvailable;
+        };
+        let digest = hasher.finalize();
+        let matched = match expected {
+            ExpectedDigest::Sri(expected) => {
+                let digest = BASE64.encode(&digest);
+                expected.iter().any(|e| *e == digest)
+            }
+            ExpectedDigest::Hex(expected) => HEXLOWER.encode(&digest) == expected,
+        };
+        if matched {
             VerifyResult::Verified
         } else {
             VerifyResult::Failed
+        }
+    }
+}
+
+/// SRI algorithms [`sri_hasher`] supports, strongest first.
+const SRI_ALGORITHMS: [&str; 5] = ["sha512", "sha3-512", "sha384", "sha3-256", "sha256"];
+
+/// The strongest supported algorithm in an SRI string and its digests,
+/// e.g. `sha1-… sha512-…` → `sha512`. Options after `?` are ignored.
+fn strongest_sri(integrity: &str) -> Option<(&'static str, Vec<String>)> {
+    let hashes: Vec<(&str, &str)> = integrity
+        .split_whitespace()
+        .map(|hash| hash.split_once('?').map_or(hash, |(hash, _)| hash))
+        .filter_map(|hash| hash.rsplit_once('-'))
+        .collect();
+    SRI_ALGORITHMS.into_iter().find_map(|algorithm| {
+        let digests: Vec<String> = hashes
+            .iter()
+            .filter(|(a, _)| *a == algorithm)
+            .map(|(_, digest)| digest.to_string())
+            .collect();
+        (!digests.is_empty()).then_some((algorithm, digests))
+    })
+}
+
+/// Hasher for an SRI algorithm (`sha256`, `sha384`, `sha512`, `sha3-256`
+/// or `sha3-512`), or `None` if the algorithm is not supported.
+fn sri_hasher(algorithm: &str) -> Option<Box<dyn DynDigest>> {
+    let hasher: Box<dyn DynDigest> = match algorithm {
+        "sha512" => Box::new(Sha512::new()),
+        "sha384" => Box::new(Sha384::new()),
+        "sha256" => Box::new(Sha256::new()),
+        "sha3-512" => Box::new(Sha3_512::new()),
+        "sha3-256" => Box::new(Sha3_256::new()),
+        _ => return None,
+    };
+    Some(hasher)
+}
+
+/// Verify a tgz against a checksum manifest.
+///
+/// `manifest` maps package-relative paths (tarball root such as
+/// `package/` stripped, as on disk after extraction) to hex SHA-256
+/// digests. Returns the manifest paths that do not match, including
+/// those missing from the archive, sorted.
+pub fn verify_tgz_manifest(
+    tgz_bytes: &[u8],
+    manifest: &HashMap<String, String>,
+) -> Result<Vec<String>> {
+    let mut archive = tar::Archive::new(decompress(tgz_bytes, ArchiveFormat::Auto)?);
+    let mut checked: HashSet<String> = HashSet::new();
+    let mut mismatched = Vec::new();
+
+    for entry in archive.entries().context("Failed to read tar entries")? {
+        let mut entry = entry.context("Failed to read tar entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path().context("Invalid tar entry path")?.into_owned();
+        let Ok(relative) = package_relative_path(&path) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().into_owned();
+        let Some(expected) = manifest.get(&relative) else {
+            continue;
         };
+
+        let mut content = Vec::with_capacity(entry_capacity(entry.size()));
+        entry
+            .read_to_end(&mut content)
+            .context(format!("Failed to read tar entry: {relative}"))?;
+        let mut hasher = Sha256::new();
+        hasher.update(&content);
+        if HEXLOWER.encode(&hasher.finalize()) != *expected {
+            mismatched.push(relative.clone());
+        }
+        checked.insert(relative);
+    }
+
+    mismatched.extend(
+        manifest
+            .keys()
+            .filter(|path| !checked.contains(*path))
+            .cloned(),
+    );
+    mismatched.sort();
+    Ok(mismatched)
+}
+
+/// Strip the tarball root component (e.g. `package/`) from an entry path.
+/// A path with a single component is kept as-is.
+///
+/// Absolute paths and `..` components are rejected, so the result is safe
+/// to join onto an extraction directory.
+pub(crate) fn package_relative_path(path: &Path) -> Result<PathBuf> {
+    if path.is_absolute() || path.components().any(|c| matches!(c, Component::ParentDir)) {
+        anyhow::bail!("malicious path in tar entry: {}", path.display());
+    }
+    let mut components = path.components();
+    components.next();
+    let rest = components.as_path();
+    Ok(if rest.as_os_str().is_empty() {
+        path.to_path_buf()
+    } else {
+        rest.to_path_buf()
+    })
+}
+
+/// Root directory prefix of a package tarball, given its entry paths,
+/// for tools that read tarballs themselves.
+///
+/// npm packs everything under `package/`, but some registries use another
+/// root (e.g. `node/` for `@types/node` tarballs from older mirrors). The
+/// root is the directory holding the top-most `package.json`:
+///
+/// - `package.json` at the archive root → `None` (nothing to strip)
+/// - `<dir>/package.json` → `Some("<dir>/")`
+/// - no `package.json` at either level → the first path component when all
+///   entries share it, else `None`
+///
+/// Extraction itself always strips the first component, like npm; for
+/// well-formed tarballs the two agree.
+pub fn determine_tar_root_prefix(entries: &[&str]) -> Option<String> {
+    if entries.contains(&"package.json") {
+        return None;
+    }
+    let manifest_dir = entries.iter().find_map(|entry| {
+        let dir = entry.strip_suffix("/package.json")?;
+        (!dir.is_empty() && !dir.contains('/')).then_some(dir)
+    });
+    if let Some(dir) = manifest_dir {
+        return Some(format!("{dir}/"));
     }
 
-    VerifyResult::NoHashAvailable
+    let (first, rest) = entries.split_first()?;
+    let (root, _) = first.split_once('/')?;
+    rest.iter()
+        .all(|entry| entry.split_once('/').is_some_and(|(r, _)| r == root))
+        .then(|| format!("{root}/"))
+}
+
+/// Decompress a package tarball (gzip or zstd) into a map of
+/// package-relative paths (see [`verify_tgz_manifest`]) to file contents.
+pub fn extract_tgz_to_map(bytes: &[u8]) -> Result<HashMap<String, Vec<u8>>> {
+    let mut archive = tar::Archive::new(decompress(bytes, ArchiveFormat::Auto)?);
+    let mut files = HashMap::new();
+    for entry in archive.entries().context("Failed to read tar archive")? {
+        let mut entry = entry.context("Failed to read tar entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let relative = package_relative_path(&entry.path().context("Invalid tar entry path")?)?;
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let relative = relative.to_string_lossy().into_owned();
+        let mut content = Vec::with_capacity(entry_capacity(entry.size()));
+        entry
+            .read_to_end(&mut content)
+            .context(format!("Failed to read file: {relative}"))?;
+        files.insert(relative, content);
+    }
+    Ok(files)
 }
 
 /// A single file entry for creating archives.
@@ -77,6 +343,50 @@ impl PackFile {
 
 /// Create a tar.gz archive from file entries.
 pub fn gzip(files: &[PackFile]) -> Result<Vec<u8>> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let files: Vec<&PackFile> = files.iter().collect();
+    write_tgz(&files, now, 1000)
+}
+
+/// Create a tarball the way `npm pack` does, ready to publish.
+///
+/// `files` are relative to the package root and stored under `package/`,
+/// with `package.json` first, zero timestamps and uid/gid 0, so packing
+/// the same files always yields the same bytes.
+pub fn npm_pack(files: &[PackFile]) -> Result<Vec<u8>> {
+    let is_manifest = |file: &PackFile| file.path == Path::new("package.json");
+    if !files.iter().any(is_manifest) {
+        anyhow::bail!("npm_pack: no package.json");
+    }
+    let mut packed = Vec::with_capacity(files.len());
+    for file in files {
+        if file.path.is_absolute()
+            || file
+                .path
+                .components()
+                .any(|c| matches!(c, Component::ParentDir))
+        {
+            anyhow::bail!(
+                "npm_pack: path outside the package: {}",
+                file.path.display()
+            );
+        }
+        packed.push(PackFile::new(
+            Path::new("package").join(&file.path),
+            file.content.clone(),
+        ));
+    }
+    // Stable sort: everything but package.json keeps its order
+    let mut packed: Vec<&PackFile> = packed.iter().collect();
+    packed.sort_by_key(|file| file.path != Path::new("package/package.json"));
+    write_tgz(&packed, 0, 0)
+}
+
+/// Write `files` as a tar.gz with the given mtime and uid/gid.
+fn write_tgz(files: &[&PackFile], mtime: u64, owner: u64) -> Result<Vec<u8>> {
     use flate2::{Compression, GzBuilder};
     use tar::Builder;
 
@@ -84,11 +394,6 @@ pub fn gzip(files: &[PackFile]) -> Result<Vec<u8>> {
     let encoder = GzBuilder::new().write(buffer, Compression::default());
     let mut archive = Builder::new(encoder);
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-
     for file in files {
         let mut header = tar::Header::new_ustar();
         header
@@ -96,9 +401,9 @@ pub fn gzip(files: &[PackFile]) -> Result<Vec<u8>> {
             .context(format!("Failed to set path: {}", file.path.display()))?;
         header.set_size(file.content.len() as u64);
         header.set_mode(0o644);
-        header.set_mtime(now);
-        header.set_uid(1000);
-        header.set_gid(1000);
+        header.set_mtime(mtime);
+        header.set_uid(owner);
+        header.set_gid(owner);
         header.set_cksum();
         archive
             .append(&header, file.content.as_slice())
@@ -114,12 +419,170 @@ pub fn gzip(files: &[PackFile]) -> Result<Vec<u8>> {
     Ok(buffer)
 }
 
+/// Read every regular file of a compressed tarball (gzip or zstd) into
+/// memory, in archive order. Paths are kept as stored (e.g. with the
+/// `package/` root).
+pub fn ungzip(bytes: &[u8]) -> Result<Vec<PackFile>> {
+    let mut archive = tar::Archive::new(decompress(bytes, ArchiveFormat::Auto)?);
+    let mut files = Vec::new();
+    for entry in archive.entries().context("Failed to read tar archive")? {
+        let mut entry = entry.context("Failed to read tar entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path().context("Invalid tar entry path")?.into_owned();
+        let mut content = Vec::with_capacity(entry_capacity(entry.size()));
+        entry
+            .read_to_end(&mut content)
+            .context(format!("Failed to read file: {}", path.display()))?;
+        files.push(PackFile { path, content });
+    }
+    Ok(files)
+}
+
+/// Compression of a package tarball.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// `.tgz` / `.tar.gz`
+    Gzip,
+    /// `.tar.zst`, served by some registry mirrors
+    Zstd,
+    /// Detect from the leading magic bytes
+    #[default]
+    Auto,
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+/// Formats recognised only to name them in the error.
+const UNSUPPORTED_MAGIC: [(&str, &[u8]); 2] = [
+    ("bzip2", b"BZh"),
+    ("xz", &[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]),
+];
+
+impl ArchiveFormat {
+    /// Format implied by the extension of a tarball URL or file name, or
+    /// [`Auto`](Self::Auto) when it has neither known extension.
+    pub fn from_url(url: &str) -> Self {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        if path.ends_with(".tar.zst") || path.ends_with(".tzst") {
+            Self::Zstd
+        } else if path.ends_with(".tgz") || path.ends_with(".tar.gz") {
+            Self::Gzip
+        } else {
+            Self::Auto
+        }
+    }
+
+    /// Resolve [`Auto`](Self::Auto) from the magic bytes of `bytes`;
+    /// other formats are returned as-is.
+    ///
+    /// bzip2 and xz tarballs are recognised but not supported; the error
+    /// names their format.
+    pub fn detect(self, bytes: &[u8]) -> Result<Self> {
+        match self {
+            Self::Auto if bytes.starts_with(&GZIP_MAGIC) => Ok(Self::Gzip),
+            Self::Auto if bytes.starts_with(&ZSTD_MAGIC) => Ok(Self::Zstd),
+            Self::Auto => match UNSUPPORTED_MAGIC
+                .iter()
+                .find(|(_, magic)| bytes.starts_with(magic))
+            {
+                Some((name, _)) => anyhow::bail!("Unsupported archive format: {name}"),
+                None => anyhow::bail!("Unrecognised archive format"),
+            },
+            known => Ok(known),
+        }
+    }
+}
+
+/// Decompressing reader over the bytes of a compressed tarball, to be
+/// wrapped in a [`tar::Archive`].
+pub fn decompress(bytes: &[u8], format: ArchiveFormat) -> Result<Box<dyn Read + '_>> {
+    use flate2::read::GzDecoder;
+    use ruzstd::decoding::StreamingDecoder;
+
+    Ok(match format.detect(bytes)? {
+        ArchiveFormat::Zstd => {
+            Box::new(StreamingDecoder::new(bytes).context("Invalid zstd archive")?)
+        }
+        // `detect` never returns `Auto`
+        ArchiveFormat::Gzip | ArchiveFormat::Auto => Box::new(GzDecoder::new(bytes)),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
     use super::*;
     use wasm_bindgen_test::*;
 
+    /// `package/package.json` = `{"name":"zst"}`, as `.tar.zst`
+    const TAR_ZST_HEX: &str = "28b52ffd640027e50200b2c40f11907d88c0866c3dbbbb75422bf0fa7f0129bec3b539a8d0e29241fb9db33a00755d8bfd81bf3986bcff9f47a93c32c411f97f3f07bdcb699f18cd2dc8160d0a2030370fee85af27e88e83b5fd07a403d94103180bc12e8b03167034d4";
+
+    #[wasm_bindgen_test]
+    fn test_archive_format_from_url() {
+        assert_eq!(
+            ArchiveFormat::from_url("a/-/a-1.0.0.tgz"),
+            ArchiveFormat::Gzip
+        );
+        assert_eq!(
+            ArchiveFormat::from_url("a-1.0.0.tar.gz?x=1"),
+            ArchiveFormat::Gzip
+        );
+        assert_eq!(
+            ArchiveFormat::from_url("a-1.0.0.tar.zst"),
+            ArchiveFormat::Zstd
+        );
+        assert_eq!(ArchiveFormat::from_url("a-1.0.0"), ArchiveFormat::Auto);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_decompress_detects_format() {
+        let read_package_json = |bytes: &[u8]| {
+            let mut archive = tar::Archive::new(decompress(bytes, ArchiveFormat::Auto).unwrap());
+            let mut entry = archive.entries().unwrap().next().unwrap().unwrap();
+            assert_eq!(entry.path().unwrap(), Path::new("package/package.json"));
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            content
+        };
+
+        let zst = HEXLOWER.decode(TAR_ZST_HEX.as_bytes()).unwrap();
+        assert_eq!(read_package_json(&zst), r#"{"name":"zst"}"#);
+
+        let tgz = gzip(&[PackFile::new("package/package.json", b"{}".to_vec())]).unwrap();
+        assert_eq!(read_package_json(&tgz), "{}");
+
+        assert!(decompress(b"not an archive", ArchiveFormat::Auto).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_archive_format_detect() {
+        let detect = |bytes: &[u8]| ArchiveFormat::Auto.detect(bytes).map_err(|e| e.to_string());
+        assert_eq!(detect(&[0x1f, 0x8b, 0x08]), Ok(ArchiveFormat::Gzip));
+        assert_eq!(
+            detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]),
+            Ok(ArchiveFormat::Zstd)
+        );
+        assert_eq!(
+            detect(b"BZh91AY&SY"),
+            Err("Unsupported archive format: bzip2".to_string())
+        );
+        assert_eq!(
+            detect(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00, 0x00]),
+            Err("Unsupported archive format: xz".to_string())
+        );
+        assert_eq!(
+            detect(b"PK\x03\x04"),
+            Err("Unrecognised archive format".to_string())
+        );
+        // Explicit formats are trusted
+        assert_eq!(
+            ArchiveFormat::Gzip.detect(b"BZh").unwrap(),
+            ArchiveFormat::Gzip
+        );
+    }
+
     #[wasm_bindgen_test]
     fn test_sig_md5() {
         assert_eq!(
@@ -128,6 +591,40 @@ mod tests {
         );
     }
 
+    #[wasm_bindgen_test]
+    fn test_sig_sha() {
+        let data = b"hello world";
+        assert_eq!(sig_sha1(data), "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+        assert_eq!(
+            sig_sha256(data),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(
+            sig_sha512(data),
+            "309ecc489c12d6eb4cc40f50c902f2b4d0ed77ee511a7c7a9bcd3ca86d4cd86f\
+             989dd35bc5ff499670da34255b45b0cfd830e81f605dcf7dc5542e93ae9cd76f"
+        );
+        let integrity = format!("sha512-{}", sig_sha512_base64(data));
+        assert!(verify_integrity(data, Some(&integrity), None).is_verified());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sig_stream_matches_in_memory() {
+        // Spans several chunks and ends mid-chunk
+        let data: Vec<u8> = (0..HASH_CHUNK_SIZE * 2 + 123)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        assert_eq!(
+            sig_md5_stream(&mut data.as_slice()).unwrap(),
+            sig_md5(&data)
+        );
+        assert_eq!(
+            sig_sha512_stream(&mut data.as_slice()).unwrap(),
+            sig_sha512(&data)
+        );
+        assert_eq!(sig_md5_stream(&mut std::io::empty()).unwrap(), sig_md5(b""));
+    }
+
     #[wasm_bindgen_test]
     fn test_verify_integrity_sha512() {
         let data = b"hello world";
@@ -136,6 +633,50 @@ mod tests {
         assert!(verify_integrity(data, Some("sha512-bad"), None).is_failed());
     }
 
+    #[wasm_bindgen_test]
+    fn test_verify_integrity_sha384() {
+        let data = b"hello world";
+        let good = "sha384-/b2OdaZ/KfcBpOBAOF4uI5hjA+oQI5IRr5B/y7g1eLPkF8txzmRu/QgZ3YwIjeG9";
+        assert!(verify_integrity(data, Some(good), None).is_verified());
+        assert!(verify_integrity(data, Some("sha384-bad"), None).is_failed());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_integrity_sha256() {
+        let data = b"hello world";
+        let good = "sha256-uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=";
+        assert!(verify_integrity(data, Some(good), None).is_verified());
+        assert!(verify_integrity(data, Some("sha256-bad"), None).is_failed());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_integrity_sha3() {
+        let data = b"hello world";
+        let good_256 = "sha3-256-ZEvMflZDcwQJmarInnYi88px+6HZcv2Uoxw7+/JOOTg=";
+        let good_512 = "sha3-512-hAAGZT6ayelRF6FckVyquBZikY6SXengBPd0/4LXB5pA1NJ7GzcmV8YdRtRwMEyIx4izpFJ60HTR3MvuXbqpmg==";
+        assert!(verify_integrity(data, Some(good_256), None).is_verified());
+        assert!(verify_integrity(data, Some(good_512), None).is_verified());
+        assert!(verify_integrity(data, Some("sha3-256-bad"), None).is_failed());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_integrity_multiple_hashes() {
+        let data = b"hello world";
+        let sha512 = "sha512-MJ7MSJwS1utMxA9QyQLytNDtd+5RGnx6m808qG1M2G+YndNbxf9JlnDaNCVbRbDP2DDoH2Bdz33FVC6TrpzXbw==";
+        let sha256 = "sha256-uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=";
+
+        let mixed = format!("sha1-KqVqHs {sha256} {sha512}");
+        assert!(verify_integrity(data, Some(&mixed), None).is_verified());
+        // Only the strongest algorithm counts
+        let weak_only_good = format!("{sha256} sha512-bad");
+        assert!(verify_integrity(data, Some(&weak_only_good), None).is_failed());
+        // Any digest of that algorithm may match
+        let rotated = format!("sha512-old {sha512}");
+        assert!(verify_integrity(data, Some(&rotated), None).is_verified());
+        let with_options = format!("{sha512}?foo");
+        assert!(verify_integrity(data, Some(&with_options), None).is_verified());
+    }
+
     #[wasm_bindgen_test]
     fn test_verify_integrity_sha1() {
         let data = b"hello world";
@@ -152,6 +693,21 @@ mod tests {
         );
     }
 
+    #[wasm_bindgen_test]
+    fn test_integrity_hasher_chunked() {
+        let data = b"hello world";
+        let integrity = "sha512-MJ7MSJwS1utMxA9QyQLytNDtd+5RGnx6m808qG1M2G+YndNbxf9JlnDaNCVbRbDP2DDoH2Bdz33FVC6TrpzXbw==";
+        let mut hasher = IntegrityHasher::new(Some(integrity), None);
+        for chunk in data.chunks(3) {
+            hasher.update(chunk);
+        }
+        assert!(hasher.finish().is_verified());
+
+        let mut hasher = IntegrityHasher::new(None, Some("bad"));
+        hasher.update(data);
+        assert!(hasher.finish().is_failed());
+    }
+
     #[wasm_bindgen_test]
     fn test_gzip_roundtrip() {
         let files = vec![
@@ -160,5 +716,129 @@ mod tests {
         ];
         let bytes = gzip(&files).unwrap();
         assert!(!bytes.is_empty());
+
+        let unpacked = ungzip(&bytes).unwrap();
+        assert_eq!(unpacked.len(), files.len());
+        for (original, unpacked) in files.iter().zip(&unpacked) {
+            assert_eq!(unpacked.path, original.path);
+            assert_eq!(unpacked.content, original.content);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_npm_pack() {
+        let files = vec![
+            PackFile::new("index.js", b"module.exports = 1".to_vec()),
+            PackFile::new("package.json", br#"{"name":"a"}"#.to_vec()),
+        ];
+        let bytes = npm_pack(&files).unwrap();
+        // Reproducible
+        assert_eq!(bytes, npm_pack(&files).unwrap());
+
+        let decoder = flate2::read::GzDecoder::new(bytes.as_slice());
+        let mut archive = tar::Archive::new(decoder);
+        let headers: Vec<_> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let header = entry.header();
+                (
+                    entry.path().unwrap().display().to_string(),
+                    header.mtime().unwrap(),
+                    header.uid().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            headers,
+            [
+                ("package/package.json".to_string(), 0, 0),
+                ("package/index.js".to_string(), 0, 0),
+            ]
+        );
+
+        assert!(npm_pack(&files[..1]).is_err());
+        let escaping = PackFile::new("../x", Vec::new());
+        assert!(npm_pack(&[files[1].clone(), escaping]).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_determine_tar_root_prefix() {
+        // No prefix: files at the archive root
+        assert_eq!(determine_tar_root_prefix(&["index.js", "lib/a.js"]), None);
+        // npm's `package/` root
+        assert_eq!(
+            determine_tar_root_prefix(&["package/index.js", "package/package.json"]),
+            Some("package/".to_string())
+        );
+        // Custom root, found through its package.json
+        assert_eq!(
+            determine_tar_root_prefix(&[
+                "node/index.d.ts",
+                "node/package.json",
+                "node/ts4.8/package.json"
+            ]),
+            Some("node/".to_string())
+        );
+        // No package.json: a shared first component is the root
+        assert_eq!(
+            determine_tar_root_prefix(&["dist/a.js", "dist/lib/b.js"]),
+            Some("dist/".to_string())
+        );
+        assert_eq!(determine_tar_root_prefix(&["a/x.js", "b/y.js"]), None);
+        assert_eq!(determine_tar_root_prefix(&[]), None);
+        // package.json at the root only, even with nested manifests
+        assert_eq!(
+            determine_tar_root_prefix(&["package.json", "sub/package.json"]),
+            None
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_extract_tgz_to_map() {
+        let tgz = gzip(&[
+            PackFile::new("package/package.json", br#"{"name":"a"}"#.to_vec()),
+            PackFile::new("package/lib/index.js", b"export {}".to_vec()),
+            PackFile::new("README", b"root-level".to_vec()),
+        ])
+        .unwrap();
+        let files = extract_tgz_to_map(&tgz).unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files["package.json"], br#"{"name":"a"}"#);
+        assert_eq!(files["lib/index.js"], b"export {}");
+        assert_eq!(files["README"], b"root-level");
+
+        assert!(package_relative_path(Path::new("package/../../etc/passwd")).is_err());
+        assert!(package_relative_path(Path::new("/etc/passwd")).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_tgz_manifest() {
+        let files = vec![
+            PackFile::new("package/a.txt", b"hello".to_vec()),
+            PackFile::new("package/d/b.txt", b"tampered".to_vec()),
+        ];
+        let bytes = gzip(&files).unwrap();
+
+        let manifest: HashMap<String, String> = [
+            (
+                "a.txt",
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            ),
+            (
+                "d/b.txt",
+                "486ea46224d1bb4fb680f34f7c9ad96a8f24ec88be73ea8e5a6c65260e9cb8a7",
+            ),
+            ("missing.txt", "00"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        assert_eq!(
+            verify_tgz_manifest(&bytes, &manifest).unwrap(),
+            vec!["d/b.txt".to_string(), "missing.txt".to_string()]
+        );
     }
 }
@@ -1,3 +1,6 @@
+use crate::fuse_fs::ReadDirStrategy;
+use crate::registry::RegistryConfig;
+
 /// Configuration for an [`OpfsProject`](crate::OpfsProject) instance.
 ///
 /// All fields have sensible defaults via [`Config::default()`].
@@ -9,10 +12,20 @@ pub struct Config {
     pub fuse_cache_max_entries: usize,
     /// Maximum concurrent HTTP downloads (default: 20)
     pub max_concurrent_downloads: usize,
-    /// Number of retry attempts for failed downloads (default: 3)
+    /// Download attempts per package, including the first; 0 counts as 1
+    /// (default: 3)
     pub download_retries: u32,
     /// Base delay in ms for exponential back-off between retries (default: 500)
     pub retry_base_delay_ms: u64,
+    /// Responses larger than this many bytes are streamed to the store
+    /// instead of buffered in memory (default: 5 MiB)
+    pub stream_download_threshold: u64,
+    /// Default and per-scope registries (default: public npm registry)
+    pub registry: RegistryConfig,
+    /// How `read_dir` lists, and `read`/`metadata` resolve, linked
+    /// directories that also hold real entries; `Overlay` costs one extra
+    /// lookup per read (default: [`ReadDirStrategy::Overlay`])
+    pub read_dir_strategy: ReadDirStrategy,
 }
 
 impl Default for Config {
@@ -23,6 +36,9 @@ impl Default for Config {
             max_concurrent_downloads: 20,
             download_retries: 3,
             retry_base_delay_ms: 500,
+            stream_download_threshold: 5 * 1024 * 1024,
+            registry: RegistryConfig::default(),
+            read_dir_strategy: ReadDirStrategy::default(),
         }
     }
 }
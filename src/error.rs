@@ -33,6 +33,12 @@ pub enum OpfsError {
     Io(std::io::Error),
     /// HTTP request returned a non-success status code
     Http { status: u16, url: String },
+    /// HTTP 429 — the registry asked us to slow down
+    RateLimited {
+        url: String,
+        /// Seconds from the `Retry-After` header, when given as a number
+        retry_after: Option<u64>,
+    },
     /// Network / transport error from reqwest
     Network(reqwest::Error),
     /// Integrity check (sha512/sha1) failed after download
@@ -52,6 +58,7 @@ impl fmt::Display for OpfsError {
         match self {
             Self::Io(e) => write!(f, "I/O error: {e}"),
             Self::Http { status, url } => write!(f, "HTTP {status} for {url}"),
+            Self::RateLimited { url, .. } => write!(f, "HTTP 429 (rate limited) for {url}"),
             Self::Network(e) => write!(f, "network error: {e}"),
             Self::IntegrityFailed { package, version } => {
                 write!(f, "{package}@{version}: integrity check failed")
@@ -64,6 +71,18 @@ impl fmt::Display for OpfsError {
     }
 }
 
+impl OpfsError {
+    /// Whether retrying the operation may succeed: network errors, 5xx
+    /// responses and rate limiting. Other 4xx responses are permanent.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Network(_) | Self::RateLimited { .. } => true,
+            Self::Http { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
 impl std::error::Error for OpfsError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
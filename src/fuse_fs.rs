@@ -4,23 +4,32 @@
 //! `node_modules/<pkg>/` directory. Its content points to an extracted
 //! directory in the store, enabling transparent reads.
 
+// The link cache lock is only ever taken between awaits. Holding a guard
+// across an `.await` would block every other task on the same thread.
+#![deny(clippy::await_holding_lock)]
+
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::{Error, ErrorKind, Read, Result};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
 use bytes::Bytes;
-use flate2::read::GzDecoder;
 use tar::Archive;
 use tokio_fs_ext::DirEntry;
-use tracing::warn;
+use tracing::{Span, debug, field, instrument, warn};
+
+use crate::archive::{ArchiveFormat, decompress, entry_capacity, package_relative_path};
+use crate::store::{resolved_marker_path, write_atomic};
 
 // ── FuseLink (typed representation) ──────────────────────────────────────
 
 /// Parsed representation of a `fuse.link` file.
 ///
-/// Format on disk: a single line containing the absolute OPFS path to
-/// the extracted package directory.
+/// Format on disk: a first line containing the absolute OPFS path to
+/// the extracted package directory, optionally followed by a `dev`
+/// marker line for development links created by
+/// [`FuseFs::create_dev_link`].
 ///
 /// Wrapped in `Arc` when cached to avoid cloning `PathBuf` on every
 /// cache hit.
@@ -28,28 +37,76 @@ use tracing::warn;
 pub struct FuseLink {
     /// Absolute OPFS path to the extracted package directory.
     pub target_dir: PathBuf,
+    /// `true` for `npm link`-style links to a local directory outside
+    /// the store.
+    pub dev: bool,
 }
 
+/// Marker line written after the target path of a development link.
+const DEV_LINK_MARKER: &str = "dev";
+
 impl FuseLink {
-    /// Parse the first line of a fuse.link file.
+    /// Parse a fuse.link file.
     pub fn parse(content: &str) -> Option<Self> {
-        let line = content.lines().next()?.trim();
+        let mut lines = content.lines();
+        let line = lines.next()?.trim();
         if line.is_empty() {
             return None;
         }
         // Strip legacy `|prefix` suffix if present (backwards compat)
         let path = line.split_once('|').map_or(line, |(p, _)| p);
+        let dev = lines.next().is_some_and(|l| l.trim() == DEV_LINK_MARKER);
         Some(Self {
             target_dir: PathBuf::from(path),
+            dev,
         })
     }
 
     /// Serialise back to the on-disk format.
     pub fn to_content(&self) -> String {
-        format!("{}\n", self.target_dir.display())
+        if self.dev {
+            format!("{}\n{DEV_LINK_MARKER}\n", self.target_dir.display())
+        } else {
+            format!("{}\n", self.target_dir.display())
+        }
+    }
+}
+
+/// Options for [`FuseFs::create_fuse_link_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuseLinkOptions {
+    /// Replace an existing `fuse.link` (default: `true`). When `false`,
+    /// an existing link fails with [`ErrorKind::AlreadyExists`].
+    pub overwrite: bool,
+    /// Check that the target directory exists first (default: `false`).
+    /// A missing target fails with [`ErrorKind::NotFound`].
+    pub validate_source: bool,
+}
+
+impl Default for FuseLinkOptions {
+    fn default() -> Self {
+        Self {
+            overwrite: true,
+            validate_source: false,
+        }
     }
 }
 
+/// How [`FuseFs::try_read_dir_with_strategy`] lists a linked directory
+/// that also has real entries next to its `fuse.link` (e.g. a nested
+/// `node_modules`), and whether reads see those entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadDirStrategy {
+    /// List and read the link target only
+    FirstMatch,
+    /// Merge the link target with the real entries; a real entry wins
+    /// over a target entry of the same name, in listings as well as in
+    /// [`FuseFs::try_read_with_strategy`] and
+    /// [`FuseFs::try_metadata_with_strategy`]
+    #[default]
+    Overlay,
+}
+
 /// Max concurrent OPFS writes during tgz extraction.
 const EXTRACTION_CONCURRENCY: usize = 64;
 
@@ -99,6 +156,101 @@ impl BoundedCache {
         self.map.clear();
         self.order.clear();
     }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Arc<FuseLink>)> {
+        self.map.iter()
+    }
+
+    fn remove(&mut self, key: &Path) {
+        if self.map.remove(key).is_some() {
+            self.order.retain(|k| k != key);
+        }
+    }
+
+    /// Remove every entry whose key lies under `dir`.
+    fn remove_under(&mut self, dir: &Path) {
+        self.map.retain(|k, _| !k.starts_with(dir));
+        self.order.retain(|k| !k.starts_with(dir));
+    }
+
+    /// Remove every entry whose link target lies under `dir`.
+    fn remove_targets_under(&mut self, dir: &Path) {
+        let map = &mut self.map;
+        map.retain(|_, link| !link.target_dir.starts_with(dir));
+        self.order.retain(|k| map.contains_key(k));
+    }
+
+    /// Change the capacity, evicting the oldest entries that no longer fit.
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.map.len() > self.capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.map.remove(&oldest);
+        }
+    }
+}
+
+impl std::fmt::Debug for BoundedCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoundedCache")
+            .field("entries", &self.map.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+/// Snapshot of the fuse-link cache, see [`FuseFs::cache_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuseCacheStats {
+    /// Cached fuse links
+    pub entry_count: usize,
+    /// Capacity before the oldest entry is evicted
+    pub max_entries: usize,
+    /// Link lookups answered from the cache
+    pub hits: u64,
+    /// Link lookups that had to read `fuse.link` from disk
+    pub misses: u64,
+}
+
+impl FuseCacheStats {
+    /// Share of link lookups answered from the cache, `0.0` before the
+    /// first lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A `fuse.link` file and the directory it points to, see
+/// [`FuseFs::cached_links`] and [`FuseFs::scan_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuseLinkInfo {
+    /// Path of the `fuse.link` file itself
+    pub link_file: PathBuf,
+    /// Directory the link resolves to
+    pub target_dir: PathBuf,
+    /// `true` for development links, see [`FuseFs::create_dev_link`]
+    pub dev: bool,
+}
+
+impl FuseLinkInfo {
+    fn new(link_file: PathBuf, link: &FuseLink) -> Self {
+        Self {
+            link_file,
+            target_dir: link.target_dir.clone(),
+            dev: link.dev,
+        }
+    }
 }
 
 // ── FuseFs ───────────────────────────────────────────────────────────────
@@ -107,14 +259,25 @@ impl BoundedCache {
 ///
 /// Uses a bounded FIFO cache to avoid repeated disk reads for fuse.link
 /// files. When the cache is full, the oldest entry is evicted.
+///
+/// `Debug` prints a cache summary (entry count and capacity), not the
+/// cached links.
+#[derive(Debug)]
 pub struct FuseFs {
+    /// Locked briefly for lookups and inserts, never across an `.await`
+    /// (the module denies `clippy::await_holding_lock`), so contention
+    /// stays negligible on read-heavy workloads.
     link_cache: RwLock<BoundedCache>,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
 }
 
 impl FuseFs {
     pub fn new(fuse_cache_max_entries: usize) -> Self {
         Self {
             link_cache: RwLock::new(BoundedCache::new(fuse_cache_max_entries)),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
         }
     }
 
@@ -124,38 +287,158 @@ impl FuseFs {
     /// of same version). The cache is always refreshed so callers never
     /// observe a stale link after this returns, even on eviction.
     pub async fn create_fuse_link(&self, target_dir: &Path, dst: &Path) -> Result<()> {
-        let fuse_link_path = dst.join("fuse.link");
-        let link = Arc::new(FuseLink {
+        self.create_fuse_link_with_options(target_dir, dst, &FuseLinkOptions::default())
+            .await
+    }
+
+    /// Create a fuse link with explicit [`FuseLinkOptions`].
+    #[instrument(
+        skip_all,
+        fields(target_dir = %target_dir.display(), dst = %dst.display())
+    )]
+    pub async fn create_fuse_link_with_options(
+        &self,
+        target_dir: &Path,
+        dst: &Path,
+        opts: &FuseLinkOptions,
+    ) -> Result<()> {
+        let link = FuseLink {
             target_dir: target_dir.to_path_buf(),
-        });
-        let link_content = link.to_content();
+            dev: false,
+        };
+        self.write_link(link, dst, opts).await
+    }
 
-        let existing = tokio_fs_ext::read(&fuse_link_path).await.ok();
-        if existing.is_none() {
-            tokio_fs_ext::create_dir_all(dst).await?;
-        }
-        if existing.as_deref() != Some(link_content.as_bytes()) {
-            tokio_fs_ext::write(&fuse_link_path, &link_content).await?;
+    /// Validate a fuse link without writing anything to OPFS.
+    ///
+    /// Checks that `target_dir` is an existing directory and that the
+    /// parent of `dst` exists, then logs at `debug` what
+    /// [`create_fuse_link`](Self::create_fuse_link) would write.
+    pub async fn create_fuse_link_dry_run(&self, target_dir: &Path, dst: &Path) -> Result<()> {
+        if !tokio_fs_ext::metadata(target_dir)
+            .await
+            .is_ok_and(|m| m.is_dir())
+        {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("fuse link source not found: {}", target_dir.display()),
+            ));
         }
 
-        if let Ok(mut cache) = self.link_cache.write() {
-            cache.put(fuse_link_path, link);
-        } else {
-            warn!("fuse link cache lock poisoned");
+        let parent = dst
+            .parent()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "fuse link dst has no parent"))?;
+        if tokio_fs_ext::metadata(parent).await.is_err() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("fuse link parent not found: {}", parent.display()),
+            ));
         }
+
+        let fuse_link_path = dst.join("fuse.link");
+        debug!(
+            "dry run: would link {} -> {}",
+            fuse_link_path.display(),
+            target_dir.display()
+        );
         Ok(())
     }
 
-    /// Try to read a file through fuse-link indirection.
+    /// Create a development link: like [`create_fuse_link`](Self::create_fuse_link)
+    /// but `target_dir` is a local directory outside the store, and the
+    /// link is marked so it can be told apart from store links.
+    pub async fn create_dev_link(&self, target_dir: &Path, dst: &Path) -> Result<()> {
+        let link = FuseLink {
+            target_dir: target_dir.to_path_buf(),
+            dev: true,
+        };
+        self.write_link(link, dst, &FuseLinkOptions::default())
+            .await
+    }
+
+    /// Create a fuse link for each `(target_dir, dst)` pair with at most
+    /// `concurrency` writes in flight, all or nothing.
+    ///
+    /// If any link fails, every link this call wrote is rolled back: new
+    /// links are removed (see [`unlink`](Self::unlink)) and replaced ones
+    /// get their previous target back. Rollback is best effort; its
+    /// failures are logged. Returns the first error, prefixed with its
+    /// destination.
+    ///
+    /// Install links each package this way, at all its paths or none.
+    pub async fn create_fuse_links(
+        &self,
+        links: &[(&Path, &Path)],
+        concurrency: usize,
+    ) -> Result<()> {
+        use futures::stream::{self, StreamExt};
+
+        let results: Vec<_> = stream::iter(links.iter().map(|&(target_dir, dst)| async move {
+            let previous = self
+                .read_fuse_link(&dst.join("fuse.link"))
+                .await
+                .ok()
+                .flatten();
+            let result = self.create_fuse_link(target_dir, dst).await;
+            (dst, previous, result)
+        }))
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+        let mut first_error = None;
+        let mut written = Vec::new();
+        for (dst, previous, result) in results {
+            match result {
+                Ok(()) => written.push((dst, previous)),
+                Err(e) => {
+                    first_error.get_or_insert_with(|| {
+                        Error::new(e.kind(), format!("{}: {e}", dst.display()))
+                    });
+                }
+            }
+        }
+        let Some(error) = first_error else {
+            return Ok(());
+        };
+
+        for (dst, previous) in written {
+            let rollback = match previous {
+                Some(link) => {
+                    self.write_link(FuseLink::clone(&link), dst, &FuseLinkOptions::default())
+                        .await
+                }
+                None => self.unlink(dst).await,
+            };
+            if let Err(e) = rollback {
+                warn!("rolling back fuse link {} failed: {e}", dst.display());
+            }
+        }
+        Err(error)
+    }
+
+    /// Try to read a file through fuse-link indirection, with
+    /// [`ReadDirStrategy::Overlay`].
     ///
     /// Returns `Ok(None)` if the path has no fuse link.
     pub async fn try_read(&self, path: &Path) -> Result<Option<Bytes>> {
-        let resolved = match self.resolve(path).await? {
-            Some(r) => r,
-            None => return Ok(None),
-        };
+        self.try_read_with_strategy(path, ReadDirStrategy::Overlay)
+            .await
+    }
 
-        let real_path = resolved.link.target_dir.join(&resolved.relative);
+    /// Try to read a file through fuse-link indirection. Under
+    /// [`ReadDirStrategy::Overlay`] a real file at `path` shadows the link
+    /// target's, as in [`try_read_dir_with_strategy`](Self::try_read_dir_with_strategy).
+    ///
+    /// Returns `Ok(None)` if the path has no fuse link.
+    pub async fn try_read_with_strategy(
+        &self,
+        path: &Path,
+        strategy: ReadDirStrategy,
+    ) -> Result<Option<Bytes>> {
+        let Some(real_path) = self.overlay_path(path, strategy).await? else {
+            return Ok(None);
+        };
         match tokio_fs_ext::read(&real_path).await {
             Ok(v) => Ok(Some(Bytes::from(v))),
             Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
@@ -163,10 +446,233 @@ impl FuseFs {
         }
     }
 
-    /// Try to read a directory through fuse-link indirection.
+    /// Try to write a file under a fuse link, copy-on-write: the file is
+    /// written as a real entry at `path`, next to `fuse.link`, where it
+    /// shadows the target's under [`ReadDirStrategy::Overlay`]. The store
+    /// directory is shared by every project linking it and is left as is.
+    /// Missing parent directories are created.
+    ///
+    /// Returns `Ok(false)` if the path has no fuse link.
+    pub async fn try_write(&self, path: &Path, contents: &[u8]) -> Result<bool> {
+        let resolved = match self.resolve(path).await? {
+            Some(r) => r,
+            None => return Ok(false),
+        };
+        if resolved.relative.as_os_str().is_empty() {
+            return Err(Error::new(
+                ErrorKind::IsADirectory,
+                format!("is a directory: {}", path.display()),
+            ));
+        }
+
+        if let Some(parent) = path.parent() {
+            tokio_fs_ext::create_dir_all(parent).await?;
+        }
+        tokio_fs_ext::write(path, contents).await?;
+        Ok(true)
+    }
+
+    /// Path in the store that `path` refers to through its fuse link, or
+    /// `None` if the path has no fuse link.
+    pub async fn try_real_path(&self, path: &Path) -> Result<Option<PathBuf>> {
+        Ok(self
+            .resolve(path)
+            .await?
+            .map(|resolved| resolved.link.target_dir.join(&resolved.relative)))
+    }
+
+    /// Try to create a directory (and its parents) under a fuse link. Like
+    /// [`try_write`](Self::try_write), the directory is created as a real
+    /// entry at `path`; the shared store is left as is.
+    ///
+    /// Returns `Ok(false)` if the path has no fuse link, is the linked
+    /// package directory itself, or lies in the package's nested
+    /// `node_modules` — nested dependencies are real directories with their
+    /// own links, as laid out by install.
+    pub async fn try_create_dir_all(&self, path: &Path) -> Result<bool> {
+        let resolved = match self.resolve(path).await? {
+            Some(r) => r,
+            None => return Ok(false),
+        };
+        match resolved.relative.components().next() {
+            None => return Ok(false),
+            Some(first) if first.as_os_str() == "node_modules" => return Ok(false),
+            Some(_) => {}
+        }
+
+        tokio_fs_ext::create_dir_all(path).await?;
+        Ok(true)
+    }
+
+    /// Try to remove a file under a fuse link. Only the real entry at
+    /// `path` (see [`try_write`](Self::try_write)) is removed; a store copy
+    /// of the same name then shows through again. The shared store is never
+    /// changed: a file that only exists there fails with `PermissionDenied`.
+    ///
+    /// Returns `Ok(false)` if the path has no fuse link or the file does
+    /// not exist.
+    pub async fn try_remove_file(&self, path: &Path) -> Result<bool> {
+        let resolved = match self.resolve(path).await? {
+            Some(r) => r,
+            None => return Ok(false),
+        };
+        if resolved.relative.as_os_str().is_empty() {
+            return Err(Error::new(
+                ErrorKind::IsADirectory,
+                format!("is a directory: {}", path.display()),
+            ));
+        }
+
+        match tokio_fs_ext::remove_file(path).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                self.deny_store_only(path, &resolved).await?;
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Try to remove a directory through fuse-link indirection.
+    ///
+    /// A linked package directory itself is removed with
+    /// [`remove_fuse_link`](Self::remove_fuse_link). Inside it, only the
+    /// real directory at `path` goes, like in
+    /// [`try_remove_file`](Self::try_remove_file); a directory that only
+    /// exists in the shared store fails with `PermissionDenied`. Returns
+    /// `Ok(false)` if the path has no fuse link or does not exist.
+    pub async fn try_remove_dir_all(&self, path: &Path) -> Result<bool> {
+        let resolved = match self.resolve(path).await? {
+            Some(r) => r,
+            None => return Ok(false),
+        };
+        if resolved.relative.as_os_str().is_empty() {
+            self.remove_fuse_link(path).await?;
+            return Ok(true);
+        }
+
+        match tokio_fs_ext::remove_dir_all(path).await {
+            Ok(()) => {
+                self.evict_links_under(path);
+                Ok(true)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                self.deny_store_only(path, &resolved).await?;
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fail with `PermissionDenied` if `path`, missing as a real entry,
+    /// exists in the store its link points to: removing it would change the
+    /// package for every project sharing the store.
+    async fn deny_store_only(&self, path: &Path, resolved: &Resolved) -> Result<()> {
+        let store_path = resolved.link.target_dir.join(&resolved.relative);
+        if tokio_fs_ext::metadata(&store_path).await.is_ok() {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!(
+                    "cannot remove inside a fuse-linked package: {}",
+                    path.display()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Remove the package linked at `dst`: the `fuse.link` and `dst` itself
+    /// with anything else in it (e.g. a nested `node_modules`), evicting
+    /// every cached link under `dst`.
+    ///
+    /// The link target is kept: store directories are shared by every
+    /// install path and project linked to the same tarball. Reclaim unused
+    /// ones with `OpfsProject::prune_store`.
+    pub async fn remove_fuse_link(&self, dst: &Path) -> Result<()> {
+        let fuse_link_path = dst.join("fuse.link");
+        if self.read_fuse_link(&fuse_link_path).await?.is_none() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("no fuse link at {}", dst.display()),
+            ));
+        }
+
+        tokio_fs_ext::remove_dir_all(dst).await?;
+        self.evict_links_under(dst);
+        Ok(())
+    }
+
+    /// Undo [`create_fuse_link`](Self::create_fuse_link): remove
+    /// `dst/fuse.link` and the then-empty `dst`, and evict the cached link.
+    /// The linked store directory is kept.
+    ///
+    /// Fails with [`ErrorKind::DirectoryNotEmpty`], before removing
+    /// anything, if `dst` holds real files besides the link.
+    pub async fn unlink(&self, dst: &Path) -> Result<()> {
+        let fuse_link_path = dst.join("fuse.link");
+        if self.read_fuse_link(&fuse_link_path).await?.is_none() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("no fuse link at {}", dst.display()),
+            ));
+        }
+
+        let others: Vec<_> = read_dir_direct(dst)
+            .await?
+            .into_iter()
+            .map(|entry| entry.file_name())
+            .filter(|name| name != "fuse.link")
+            .collect();
+        if !others.is_empty() {
+            return Err(Error::new(
+                ErrorKind::DirectoryNotEmpty,
+                format!(
+                    "cannot unlink {}: it also contains {others:?}",
+                    dst.display()
+                ),
+            ));
+        }
+
+        tokio_fs_ext::remove_file(&fuse_link_path).await?;
+        tokio_fs_ext::remove_dir_all(dst).await?;
+        self.evict_links_under(dst);
+        Ok(())
+    }
+
+    /// Evict every cached fuse link under `dir`, e.g. after removing it
+    /// from disk.
+    pub fn evict_links_under(&self, dir: &Path) {
+        if let Ok(mut cache) = self.link_cache.write() {
+            cache.remove_under(dir);
+        }
+    }
+
+    /// Evict every cached fuse link whose target lies under `dir`, e.g.
+    /// after removing a package from the store.
+    pub fn evict_links_into(&self, dir: &Path) {
+        if let Ok(mut cache) = self.link_cache.write() {
+            cache.remove_targets_under(dir);
+        }
+    }
+
+    /// Try to read a directory through fuse-link indirection, overlaying
+    /// real entries on the link target (see [`ReadDirStrategy::Overlay`]).
     ///
     /// Returns `Ok(None)` if the path has no fuse link.
     pub async fn try_read_dir(&self, path: &Path) -> Result<Option<Vec<DirEntry>>> {
+        self.try_read_dir_with_strategy(path, ReadDirStrategy::Overlay)
+            .await
+    }
+
+    /// Try to read a directory through fuse-link indirection with an
+    /// explicit [`ReadDirStrategy`]. `fuse.link` itself is never listed.
+    ///
+    /// Returns `Ok(None)` if the path has no fuse link.
+    pub async fn try_read_dir_with_strategy(
+        &self,
+        path: &Path,
+        strategy: ReadDirStrategy,
+    ) -> Result<Option<Vec<DirEntry>>> {
         let resolved = match self.resolve(path).await? {
             Some(r) => r,
             None => return Ok(None),
@@ -177,47 +683,56 @@ impl FuseFs {
             Ok(entries) => entries,
             Err(_) => return Ok(None),
         };
+        if strategy == ReadDirStrategy::FirstMatch {
+            return Ok(Some(target_entries));
+        }
 
         // Issue #7: Merge with real files only when necessary. Most fuse-linked
         // directories only contain `fuse.link`, making the full merge redundant.
-        match read_dir_direct(path).await {
-            Ok(original) => {
-                // Fast path: check if any non-fuse.link entries exist
-                let has_extra_files = original
-                    .iter()
-                    .any(|e| e.file_name().to_string_lossy() != "fuse.link");
-
-                if !has_extra_files {
-                    return Ok(Some(target_entries));
-                }
-
-                // Slow path: merge original entries with target entries
-                let target_names: HashSet<_> =
-                    target_entries.iter().map(|e| e.file_name()).collect();
-                let mut combined: Vec<_> = original
-                    .into_iter()
-                    .filter(|e| {
-                        e.file_name().to_string_lossy() != "fuse.link"
-                            && !target_names.contains(&e.file_name())
-                    })
-                    .collect();
-                combined.extend(target_entries);
-                Ok(Some(combined))
-            }
-            Err(_) => Ok(Some(target_entries)),
+        let original: Vec<_> = match read_dir_direct(path).await {
+            Ok(entries) => entries
+                .into_iter()
+                .filter(|e| e.file_name().to_string_lossy() != "fuse.link")
+                .collect(),
+            Err(_) => return Ok(Some(target_entries)),
+        };
+        if original.is_empty() {
+            return Ok(Some(target_entries));
         }
+
+        // Slow path: real entries shadow target entries of the same name
+        let real_names: HashSet<_> = original.iter().map(|e| e.file_name()).collect();
+        let mut combined = original;
+        combined.extend(
+            target_entries
+                .into_iter()
+                .filter(|e| !real_names.contains(&e.file_name())),
+        );
+        Ok(Some(combined))
     }
 
-    /// Try to get metadata through fuse-link indirection.
+    /// Try to get metadata through fuse-link indirection, with
+    /// [`ReadDirStrategy::Overlay`].
     ///
     /// Returns `Ok(None)` if the path has no fuse link.
     pub async fn try_metadata(&self, path: &Path) -> Result<Option<tokio_fs_ext::Metadata>> {
-        let resolved = match self.resolve(path).await? {
-            Some(r) => r,
-            None => return Ok(None),
-        };
+        self.try_metadata_with_strategy(path, ReadDirStrategy::Overlay)
+            .await
+    }
 
-        let real_path = resolved.link.target_dir.join(&resolved.relative);
+    /// Try to get metadata through fuse-link indirection; a real entry
+    /// shadows the link target's like in
+    /// [`try_read_with_strategy`](Self::try_read_with_strategy).
+    ///
+    /// Returns `Ok(None)` if the path has no fuse link.
+    pub async fn try_metadata_with_strategy(
+        &self,
+        path: &Path,
+        strategy: ReadDirStrategy,
+    ) -> Result<Option<tokio_fs_ext::Metadata>> {
+        let Some(real_path) = self.overlay_path(path, strategy).await? else {
+            return Ok(None);
+        };
         match tokio_fs_ext::metadata(&real_path).await {
             Ok(m) => Ok(Some(m)),
             Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
@@ -225,6 +740,19 @@ impl FuseFs {
         }
     }
 
+    /// Check, without I/O, whether `path` is served through a cached fuse link.
+    ///
+    /// Only consults the in-memory link cache, so a link that exists on
+    /// disk but has not been read yet reports `false`.
+    pub fn is_cached_link_path(&self, path: &Path) -> bool {
+        let Some(fuse_link_path) = locate_fuse_link_file(path) else {
+            return false;
+        };
+        self.link_cache
+            .read()
+            .is_ok_and(|cache| cache.get(&fuse_link_path).is_some())
+    }
+
     /// Pre-populate the link cache for a known fuse link (avoids disk IO on cold read).
     ///
     /// Called during install after `create_fuse_link` to ensure the cache is warm.
@@ -234,19 +762,84 @@ impl FuseFs {
         };
         let link = Arc::new(FuseLink {
             target_dir: target_dir.to_path_buf(),
+            dev: false,
         });
         if let Ok(mut cache) = self.link_cache.write() {
             cache.put(fuse_link_path, link);
         }
     }
 
+    /// Try to move the fuse link of package directory `from` to `to`.
+    ///
+    /// Writes the same link under `to`, moves any other entries of `from`
+    /// (e.g. a nested `node_modules`) along, and removes `from`. Fails with
+    /// `AlreadyExists` if `to` exists.
+    ///
+    /// Returns `Ok(false)` if `from` is not a linked package directory but a
+    /// real entry, with or without a fuse link above it (e.g. a nested
+    /// `node_modules/dep`); the caller renames it as usual. Paths only
+    /// backed by the shared store cannot be renamed.
+    pub async fn try_rename(&self, from: &Path, to: &Path) -> Result<bool> {
+        let Some(resolved) = self.resolve(from).await? else {
+            return Ok(false);
+        };
+        if !resolved.relative.as_os_str().is_empty() {
+            if tokio_fs_ext::metadata(from).await.is_ok() {
+                return Ok(false);
+            }
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!(
+                    "cannot rename inside a fuse-linked package: {}",
+                    from.display()
+                ),
+            ));
+        }
+        if tokio_fs_ext::metadata(to).await.is_ok() {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("already exists: {}", to.display()),
+            ));
+        }
+
+        self.write_link((*resolved.link).clone(), to, &FuseLinkOptions::default())
+            .await?;
+
+        let old_link = from.join("fuse.link");
+        tokio_fs_ext::remove_file(&old_link).await?;
+        if let Ok(mut cache) = self.link_cache.write() {
+            cache.remove(&old_link);
+        }
+        for entry in read_dir_direct(from).await? {
+            let name = entry.file_name();
+            tokio_fs_ext::rename(from.join(&name), to.join(&name)).await?;
+        }
+        tokio_fs_ext::remove_dir_all(from).await?;
+
+        Ok(true)
+    }
+
     /// Extract all files from a tgz into a real directory on disk.
     ///
+    /// Zstd-compressed tarballs (`.tar.zst`) are accepted too; the format is
+    /// detected from the file's magic bytes (see [`ArchiveFormat::Auto`]).
     /// Uses streaming decompression — no full decompressed buffer in memory.
     /// Returns the extraction root directory (tgz path with `.tgz` stripped).
+    ///
+    /// The span records `tgz_size`, `files_extracted` and
+    /// `total_bytes_written` once extraction completes.
+    #[instrument(
+        skip(self, tgz_path),
+        fields(
+            tgz_path = %tgz_path.display(),
+            tgz_size = field::Empty,
+            files_extracted = field::Empty,
+            total_bytes_written = field::Empty,
+        )
+    )]
     pub async fn extract_tgz_to_dir(&self, tgz_path: &Path) -> Result<PathBuf> {
         let out_dir = tgz_path.with_extension(""); // strip .tgz
-        let sentinel = PathBuf::from(format!("{}._resolved", out_dir.display()));
+        let sentinel = resolved_marker_path(&out_dir);
 
         // Skip if already extracted successfully
         if tokio_fs_ext::metadata(&sentinel).await.is_ok() {
@@ -254,7 +847,7 @@ impl FuseFs {
         }
 
         // Issue #2 & #8: Phase 1 — Read all entries in a scoped block so that the
-        // raw tgz bytes (`raw`), `GzDecoder`, and `Archive` are dropped immediately
+        // raw tgz bytes (`raw`), the decoder, and `Archive` are dropped immediately
         // after iteration, freeing compressed-data memory before the write phase.
         // Also collect unique parent directories for batch creation (Issue #8).
         struct PendingFile {
@@ -263,11 +856,14 @@ impl FuseFs {
         }
         let mut pending_files: Vec<PendingFile> = Vec::new();
         let mut unique_dirs: HashSet<PathBuf> = HashSet::new();
+        let mut total_bytes: u64 = 0;
 
         {
             let raw = tokio_fs_ext::read(tgz_path).await?;
-            let gz = GzDecoder::new(&raw[..]);
-            let mut archive = Archive::new(gz);
+            Span::current().record("tgz_size", raw.len() as u64);
+            let decoder = decompress(&raw, ArchiveFormat::Auto)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            let mut archive = Archive::new(decoder);
 
             for entry_result in archive.entries()? {
                 let mut entry = entry_result?;
@@ -275,43 +871,22 @@ impl FuseFs {
                     continue;
                 }
 
-                let path = entry.path()?.to_path_buf();
-
-                // Security: reject absolute paths and path traversal attempts
-                if path.is_absolute()
-                    || path
-                        .components()
-                        .any(|c| matches!(c, std::path::Component::ParentDir))
-                {
-                    return Err(Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("malicious path in tar entry: {}", path.display()),
-                    ));
-                }
-
-                // Strip the first component (e.g. "package/") from tar paths
-                let normalized = if let Some(first) = path.components().next() {
-                    let stripped = path.strip_prefix(first).unwrap_or(&path);
-                    if stripped.as_os_str().is_empty() {
-                        path
-                    } else {
-                        stripped.to_path_buf()
-                    }
-                } else {
-                    path
-                };
+                // Rejects absolute paths and path traversal attempts
+                let normalized = package_relative_path(&entry.path()?)
+                    .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
 
                 if normalized.as_os_str().is_empty() {
                     continue;
                 }
 
-                let mut content = Vec::new();
+                let mut content = Vec::with_capacity(entry_capacity(entry.size()));
                 entry.read_to_end(&mut content)?;
 
                 let full_path = out_dir.join(normalized);
                 if let Some(parent) = full_path.parent() {
                     unique_dirs.insert(parent.to_path_buf());
                 }
+                total_bytes += content.len() as u64;
                 pending_files.push(PendingFile {
                     path: full_path,
                     content: Bytes::from(content),
@@ -325,6 +900,8 @@ impl FuseFs {
             tokio_fs_ext::create_dir_all(dir).await?;
         }
 
+        let files_extracted = pending_files.len() as u64;
+
         // Phase 3 — Write files concurrently (no per-file create_dir_all needed).
         use futures::stream::{FuturesUnordered, StreamExt};
         let mut write_futures = FuturesUnordered::new();
@@ -346,26 +923,324 @@ impl FuseFs {
         }
 
         // Mark extraction as complete
-        tokio_fs_ext::write(&sentinel, b"").await?;
+        write_atomic(&sentinel, b"").await?;
+
+        let span = Span::current();
+        span.record("files_extracted", files_extracted);
+        span.record("total_bytes_written", total_bytes);
 
         Ok(out_dir)
     }
 
-    /// Clear the fuse-link cache.
-    pub fn clear(&self) {
-        if let Ok(mut lc) = self.link_cache.write() {
-            lc.clear();
+    /// Files of the package extracted from `tgz_path`, relative to the
+    /// extraction directory with `/` separators and sorted.
+    ///
+    /// Returns `Ok(None)` when the tgz has not been extracted (completely)
+    /// yet; nothing is extracted on demand.
+    pub async fn extracted_file_list(&self, tgz_path: &Path) -> Result<Option<Vec<String>>> {
+        let out_dir = tgz_path.with_extension("");
+        if tokio_fs_ext::metadata(resolved_marker_path(&out_dir))
+            .await
+            .is_err()
+        {
+            return Ok(None);
         }
-    }
 
-    // ── private ──────────────────────────────────────────────────────
+        let mut files = Vec::new();
+        let mut stack = vec![out_dir.clone()];
+        while let Some(dir) = stack.pop() {
+            for entry in read_dir_direct(&dir).await? {
+                let path = dir.join(entry.file_name());
+                if tokio_fs_ext::metadata(&path).await?.is_dir() {
+                    stack.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&out_dir) {
+                    files.push(relative.to_string_lossy().into_owned());
+                }
+            }
+        }
+        files.sort();
+        Ok(Some(files))
+    }
 
-    /// Resolve a path to its fuse-link target (if one exists).
-    async fn resolve(&self, path: &Path) -> Result<Option<Resolved>> {
-        let fuse_link_path = match locate_fuse_link_file(path) {
-            Some(p) => p,
-            None => return Ok(None),
-        };
+    /// Copy the package directory `source_dir` to `out_dir` for a local
+    /// (`file:` or workspace) dependency, replacing any previous copy.
+    ///
+    /// Nested `node_modules` are skipped. On success the same sentinel as
+    /// [`extract_tgz_to_dir`](Self::extract_tgz_to_dir) marks `out_dir`
+    /// complete. Returns the number of files copied.
+    pub async fn copy_package_dir(&self, source_dir: &Path, out_dir: &Path) -> Result<u64> {
+        if !tokio_fs_ext::metadata(source_dir)
+            .await
+            .is_ok_and(|m| m.is_dir())
+        {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("local package not found: {}", source_dir.display()),
+            ));
+        }
+
+        let sentinel = resolved_marker_path(out_dir);
+        let _ = tokio_fs_ext::remove_file(&sentinel).await;
+        let _ = tokio_fs_ext::remove_dir_all(out_dir).await;
+
+        let mut files_copied = 0;
+        let mut stack = vec![PathBuf::new()];
+        while let Some(relative) = stack.pop() {
+            tokio_fs_ext::create_dir_all(out_dir.join(&relative)).await?;
+            for entry in read_dir_direct(&source_dir.join(&relative)).await? {
+                let file_name = entry.file_name();
+                let relative = relative.join(&file_name);
+                let from = source_dir.join(&relative);
+                if tokio_fs_ext::metadata(&from).await?.is_dir() {
+                    if file_name != "node_modules" {
+                        stack.push(relative);
+                    }
+                } else {
+                    tokio_fs_ext::copy(&from, out_dir.join(&relative)).await?;
+                    files_copied += 1;
+                }
+            }
+        }
+
+        write_atomic(&sentinel, b"").await?;
+        Ok(files_copied)
+    }
+
+    /// Find every `fuse.link` file under `root` with a plain directory walk
+    /// (links are not followed). Results are sorted.
+    pub async fn find_fuse_link_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        let mut stack = vec![root.to_path_buf()];
+
+        while let Some(dir) = stack.pop() {
+            for entry in read_dir_direct(&dir).await? {
+                let path = dir.join(entry.file_name());
+                if entry.file_name().to_string_lossy() == "fuse.link" {
+                    found.push(path);
+                } else if tokio_fs_ext::metadata(&path)
+                    .await
+                    .is_ok_and(|m| m.is_dir())
+                {
+                    stack.push(path);
+                }
+            }
+        }
+
+        found.sort();
+        Ok(found)
+    }
+
+    /// Read every `fuse.link` file under `root`, for debugging. Unlike
+    /// [`cached_links`](Self::cached_links) this sees links that were
+    /// never read or have been evicted. Unparseable files are skipped.
+    /// Results are sorted by link file.
+    pub async fn scan_links(&self, root: &Path) -> Result<Vec<FuseLinkInfo>> {
+        let mut links = Vec::new();
+        for link_file in self.find_fuse_link_files(root).await? {
+            let Ok(content) = tokio_fs_ext::read_to_string(&link_file).await else {
+                continue;
+            };
+            if let Some(link) = FuseLink::parse(&content) {
+                links.push(FuseLinkInfo::new(link_file, &link));
+            }
+        }
+        Ok(links)
+    }
+
+    /// Re-point broken store links under `root` at their package in `store_root`.
+    ///
+    /// A link is broken when its target directory no longer exists. It is
+    /// repaired only if `<store_root>/<name>/-/<old target dir name>` holds
+    /// a complete extraction. Dev links are left alone.
+    ///
+    /// Returns the number of links repaired.
+    pub async fn repair_broken_links(&self, root: &Path, store_root: &Path) -> Result<usize> {
+        let mut repaired = 0;
+
+        for fuse_link_path in self.find_fuse_link_files(root).await? {
+            let Ok(content) = tokio_fs_ext::read_to_string(&fuse_link_path).await else {
+                continue;
+            };
+            let Some(link) = FuseLink::parse(&content) else {
+                continue;
+            };
+            if link.dev || tokio_fs_ext::metadata(&link.target_dir).await.is_ok() {
+                continue;
+            }
+
+            let (Some(dst), Some(dir_name)) =
+                (fuse_link_path.parent(), link.target_dir.file_name())
+            else {
+                continue;
+            };
+            let Some(name) = package_name_from_dir(dst) else {
+                continue;
+            };
+
+            let candidate = store_root.join(&name).join("-").join(dir_name);
+            let sentinel = resolved_marker_path(&candidate);
+            let complete = tokio_fs_ext::metadata(&candidate)
+                .await
+                .is_ok_and(|m| m.is_dir())
+                && tokio_fs_ext::metadata(&sentinel).await.is_ok();
+            if !complete {
+                warn!(
+                    "cannot repair {}: no extracted copy at {}",
+                    fuse_link_path.display(),
+                    candidate.display()
+                );
+                continue;
+            }
+
+            self.create_fuse_link(&candidate, dst).await?;
+            repaired += 1;
+        }
+
+        Ok(repaired)
+    }
+
+    /// Clear the fuse-link cache and reset its hit/miss counters.
+    pub fn clear(&self) {
+        if let Ok(mut lc) = self.link_cache.write() {
+            lc.clear();
+        }
+        self.cache_hits.store(0, Ordering::Relaxed);
+        self.cache_misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Resize the fuse-link cache, evicting the oldest entries if it
+    /// shrinks below the current entry count.
+    pub fn set_cache_max_entries(&self, max_entries: usize) {
+        if let Ok(mut lc) = self.link_cache.write() {
+            lc.set_capacity(max_entries);
+        }
+    }
+
+    /// Current entry count, capacity and hit/miss counters of the
+    /// fuse-link cache. Counters run from creation or the last
+    /// [`clear`](Self::clear).
+    ///
+    /// Takes only a read lock and never evicts.
+    pub fn cache_stats(&self) -> FuseCacheStats {
+        let (entry_count, max_entries) = match self.link_cache.read() {
+            Ok(lc) => (lc.len(), lc.capacity),
+            Err(_) => (0, 0),
+        };
+        FuseCacheStats {
+            entry_count,
+            max_entries,
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The links currently held in the fuse-link cache, sorted by link
+    /// file, for debugging. Takes only a read lock and does no IO.
+    pub fn cached_links(&self) -> Vec<FuseLinkInfo> {
+        let mut links: Vec<_> = match self.link_cache.read() {
+            Ok(lc) => lc
+                .iter()
+                .map(|(path, link)| FuseLinkInfo::new(path.clone(), link))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        links.sort_by(|a, b| a.link_file.cmp(&b.link_file));
+        links
+    }
+
+    /// Evict cached links whose `fuse.link` changed or disappeared on disk
+    /// since it was cached, e.g. after another worker reinstalled a
+    /// package. Returns the number of links evicted.
+    pub async fn evict_stale_links(&self) -> usize {
+        let mut evicted = 0;
+        for cached in self.cached_links() {
+            let on_disk = tokio_fs_ext::read_to_string(&cached.link_file)
+                .await
+                .ok()
+                .and_then(|content| FuseLink::parse(&content));
+            let unchanged = on_disk
+                .is_some_and(|link| link.target_dir == cached.target_dir && link.dev == cached.dev);
+            if unchanged {
+                continue;
+            }
+            if let Ok(mut lc) = self.link_cache.write() {
+                lc.remove(&cached.link_file);
+            }
+            evicted += 1;
+        }
+        evicted
+    }
+
+    // ── private ──────────────────────────────────────────────────────
+
+    /// Write `link` to `dst/fuse.link`, skipping the write when the content
+    /// is unchanged, and refresh the cache.
+    async fn write_link(&self, link: FuseLink, dst: &Path, opts: &FuseLinkOptions) -> Result<()> {
+        if opts.validate_source
+            && !tokio_fs_ext::metadata(&link.target_dir)
+                .await
+                .is_ok_and(|m| m.is_dir())
+        {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("fuse link source not found: {}", link.target_dir.display()),
+            ));
+        }
+
+        let fuse_link_path = dst.join("fuse.link");
+        let link = Arc::new(link);
+        let link_content = link.to_content();
+
+        let existing = tokio_fs_ext::read(&fuse_link_path).await.ok();
+        if existing.is_some() && !opts.overwrite {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("fuse link already exists: {}", fuse_link_path.display()),
+            ));
+        }
+        if existing.is_none() {
+            tokio_fs_ext::create_dir_all(dst).await?;
+        }
+        if existing.as_deref() != Some(link_content.as_bytes()) {
+            tokio_fs_ext::write(&fuse_link_path, &link_content).await?;
+        }
+
+        if let Ok(mut cache) = self.link_cache.write() {
+            cache.put(fuse_link_path, link);
+        } else {
+            warn!("fuse link cache lock poisoned");
+        }
+        Ok(())
+    }
+
+    /// Where a read of `path` is served from: `path` itself if it is a real
+    /// entry shadowing the link target under [`ReadDirStrategy::Overlay`],
+    /// else the link target. `None` if the path has no fuse link.
+    ///
+    /// The linked package directory itself always resolves to the target.
+    async fn overlay_path(
+        &self,
+        path: &Path,
+        strategy: ReadDirStrategy,
+    ) -> Result<Option<PathBuf>> {
+        let Some(resolved) = self.resolve(path).await? else {
+            return Ok(None);
+        };
+        if strategy == ReadDirStrategy::Overlay
+            && !resolved.relative.as_os_str().is_empty()
+            && tokio_fs_ext::metadata(path).await.is_ok()
+        {
+            return Ok(Some(path.to_path_buf()));
+        }
+        Ok(Some(resolved.link.target_dir.join(&resolved.relative)))
+    }
+
+    /// Resolve a path to its fuse-link target (if one exists).
+    async fn resolve(&self, path: &Path) -> Result<Option<Resolved>> {
+        let fuse_link_path = match locate_fuse_link_file(path) {
+            Some(p) => p,
+            None => return Ok(None),
+        };
 
         let link = match self.read_fuse_link(&fuse_link_path).await? {
             Some(l) => l,
@@ -391,9 +1266,11 @@ impl FuseFs {
         // Cache hit — lock briefly, then release
         if let Ok(cache) = self.link_cache.read() {
             if let Some(link) = cache.get(fuse_link_path) {
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
                 return Ok(Some(Arc::clone(link)));
             }
         }
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
 
         // Cache miss — read from disk.
         // Any error means the fuse.link is absent or unreadable — treat as "no link".
@@ -494,9 +1371,30 @@ fn locate_fuse_link_file(path: &Path) -> Option<PathBuf> {
     None
 }
 
+/// Package name for a `node_modules/<pkg>` or `node_modules/@scope/pkg` dir.
+fn package_name_from_dir(dir: &Path) -> Option<String> {
+    use std::path::Component;
+
+    let mut names = dir.components().rev().filter_map(|c| match c {
+        Component::Normal(name) => name.to_str(),
+        _ => None,
+    });
+    let name = names.next()?;
+    let parent = names.next()?;
+    if parent.starts_with('@') {
+        (names.next()? == "node_modules").then(|| format!("{parent}/{name}"))
+    } else {
+        (parent == "node_modules").then(|| name.to_string())
+    }
+}
+
 /// Read directory directly (no fuse-link logic).
+///
+/// Entries are sorted by file name so listings are stable across runs.
 async fn read_dir_direct(path: &Path) -> Result<Vec<DirEntry>> {
-    tokio_fs_ext::read_dir(path).await?.collect()
+    let mut entries: Vec<DirEntry> = tokio_fs_ext::read_dir(path).await?.collect::<Result<_>>()?;
+    entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(entries)
 }
 
 // ── tests ────────────────────────────────────────────────────────────────
@@ -536,10 +1434,25 @@ mod tests {
     fn test_fuse_link_roundtrip() {
         let link = FuseLink {
             target_dir: PathBuf::from("/stores/foo/-/foo-1.0.0"),
+            dev: false,
         };
         let content = link.to_content();
         let parsed = FuseLink::parse(&content).unwrap();
         assert_eq!(parsed.target_dir, link.target_dir);
+        assert!(!parsed.dev);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fuse_link_dev_roundtrip() {
+        let link = FuseLink {
+            target_dir: PathBuf::from("/workspace/my-lib"),
+            dev: true,
+        };
+        let content = link.to_content();
+        assert_eq!(content, "/workspace/my-lib\ndev\n");
+        let parsed = FuseLink::parse(&content).unwrap();
+        assert_eq!(parsed.target_dir, link.target_dir);
+        assert!(parsed.dev);
     }
 
     #[wasm_bindgen_test]
@@ -707,6 +1620,33 @@ mod tests {
         let _ = tokio_fs_ext::remove_dir_all(base).await;
     }
 
+    #[wasm_bindgen_test]
+    async fn test_extracted_file_list() {
+        use crate::archive::{PackFile, gzip};
+
+        let base = Path::new("/test_extracted_file_list");
+        let tgz_path = base.join("pkg-1.0.0.tgz");
+        let tgz = gzip(&[
+            PackFile::new("package/package.json", b"{}".to_vec()),
+            PackFile::new("package/lib/index.d.ts", b"export {}".to_vec()),
+            PackFile::new("package/lib/index.js", b"".to_vec()),
+        ])
+        .unwrap();
+        tokio_fs_ext::create_dir_all(base).await.unwrap();
+        tokio_fs_ext::write(&tgz_path, tgz).await.unwrap();
+
+        let fs = FuseFs::new(100);
+        assert_eq!(fs.extracted_file_list(&tgz_path).await.unwrap(), None);
+
+        fs.extract_tgz_to_dir(&tgz_path).await.unwrap();
+        assert_eq!(
+            fs.extracted_file_list(&tgz_path).await.unwrap().unwrap(),
+            ["lib/index.d.ts", "lib/index.js", "package.json"]
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
     #[wasm_bindgen_test]
     async fn test_extract_tgz_re_extracts_without_sentinel() {
         let base = Path::new("/test_extract_reextract");
@@ -738,6 +1678,644 @@ mod tests {
         let _ = tokio_fs_ext::remove_dir_all(base).await;
     }
 
+    #[wasm_bindgen_test]
+    async fn test_create_fuse_link_with_options() {
+        let base = Path::new("/test_fuse_link_options");
+        let dst = base.join("node_modules/foo");
+        let target = base.join("stores/foo-1.0.0");
+        let fs = FuseFs::new(100);
+
+        // validate_source rejects a missing target and writes nothing
+        let validate = FuseLinkOptions {
+            validate_source: true,
+            ..Default::default()
+        };
+        let err = fs
+            .create_fuse_link_with_options(&target, &dst, &validate)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(
+            tokio_fs_ext::metadata(&dst.join("fuse.link"))
+                .await
+                .is_err()
+        );
+
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        fs.create_fuse_link_with_options(&target, &dst, &validate)
+            .await
+            .unwrap();
+
+        // overwrite: false refuses to replace the existing link
+        let no_overwrite = FuseLinkOptions {
+            overwrite: false,
+            ..Default::default()
+        };
+        let err = fs
+            .create_fuse_link_with_options(&target, &dst, &no_overwrite)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_try_write() {
+        let base = Path::new("/test_fuse_write");
+        let dst = base.join("node_modules/pkg");
+        let target = base.join("stores/pkg-1.0.0");
+        let fs = FuseFs::new(100);
+
+        tokio_fs_ext::create_dir_all(target.join("dist"))
+            .await
+            .unwrap();
+        tokio_fs_ext::write(target.join("dist/index.js"), b"store")
+            .await
+            .unwrap();
+        fs.create_fuse_link(&target, &dst).await.unwrap();
+
+        // Copy-on-write: the store keeps its content
+        let path = dst.join("dist/index.js");
+        assert!(fs.try_write(&path, b"custom").await.unwrap());
+        assert_eq!(
+            tokio_fs_ext::read(target.join("dist/index.js"))
+                .await
+                .unwrap(),
+            b"store"
+        );
+        assert_eq!(
+            fs.try_read(&path).await.unwrap().as_deref(),
+            Some(&b"custom"[..])
+        );
+
+        // Missing parents are created
+        let nested = dst.join("lib/deep/new.js");
+        assert!(fs.try_write(&nested, b"new").await.unwrap());
+        assert!(tokio_fs_ext::metadata(target.join("lib")).await.is_err());
+        assert_eq!(
+            fs.try_read(&nested).await.unwrap().as_deref(),
+            Some(&b"new"[..])
+        );
+
+        let err = fs.try_write(&dst, b"x").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IsADirectory);
+        assert!(!fs.try_write(&base.join("a.js"), b"x").await.unwrap());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_try_create_dir_all() {
+        let base = Path::new("/test_fuse_create_dir");
+        let dst = base.join("node_modules/a");
+        let target = base.join("stores/a-1.0.0");
+        let fs = FuseFs::new(100);
+
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        fs.create_fuse_link(&target, &dst).await.unwrap();
+
+        // Inside the package: created as a real directory, not in the store
+        assert!(fs.try_create_dir_all(&dst.join("dist/esm")).await.unwrap());
+        assert!(
+            tokio_fs_ext::metadata(dst.join("dist/esm"))
+                .await
+                .is_ok_and(|m| m.is_dir())
+        );
+        assert!(tokio_fs_ext::metadata(target.join("dist")).await.is_err());
+        assert!(
+            fs.try_metadata(&dst.join("dist/esm"))
+                .await
+                .unwrap()
+                .is_some_and(|m| m.is_dir())
+        );
+
+        // Nested dependencies stay real directories
+        assert!(
+            !fs.try_create_dir_all(&dst.join("node_modules/b"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !fs.try_create_dir_all(&dst.join("node_modules"))
+                .await
+                .unwrap()
+        );
+        assert!(!fs.try_create_dir_all(&dst).await.unwrap());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_create_fuse_link_dry_run() {
+        let base = Path::new("/test_fuse_dry_run");
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+        let target = base.join("stores/a-1.0.0");
+        let dst = base.join("node_modules/a");
+        let fs = FuseFs::new(100);
+
+        // Missing source
+        let err = fs
+            .create_fuse_link_dry_run(&target, &dst)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // Missing destination parent
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        let err = fs
+            .create_fuse_link_dry_run(&target, &dst)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // Valid link: nothing is written
+        tokio_fs_ext::create_dir_all(base.join("node_modules"))
+            .await
+            .unwrap();
+        fs.create_fuse_link_dry_run(&target, &dst).await.unwrap();
+        assert!(tokio_fs_ext::metadata(&dst).await.is_err());
+        assert!(!fs.is_cached_link_path(&dst.join("index.js")));
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_remove_through_fuse_link() {
+        let base = Path::new("/test_fuse_remove");
+        let dst = base.join("node_modules/pkg");
+        let target = base.join("stores/pkg-1.0.0");
+        let fs = FuseFs::new(100);
+
+        tokio_fs_ext::create_dir_all(target.join("lib"))
+            .await
+            .unwrap();
+        tokio_fs_ext::write(target.join("extra.js"), b"x")
+            .await
+            .unwrap();
+        tokio_fs_ext::write(resolved_marker_path(&target), b"")
+            .await
+            .unwrap();
+        fs.create_fuse_link(&target, &dst).await.unwrap();
+
+        // Store-only entries cannot be removed through the link
+        let err = fs.try_remove_file(&dst.join("extra.js")).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        let err = fs.try_remove_dir_all(&dst.join("lib")).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(!fs.try_remove_file(&dst.join("missing.js")).await.unwrap());
+
+        // Real (copy-on-write) entries are removed; the store copy stays
+        tokio_fs_ext::write(dst.join("extra.js"), b"y")
+            .await
+            .unwrap();
+        tokio_fs_ext::create_dir_all(dst.join("lib")).await.unwrap();
+        tokio_fs_ext::write(dst.join("lib/a.js"), b"a")
+            .await
+            .unwrap();
+        assert!(fs.try_remove_file(&dst.join("extra.js")).await.unwrap());
+        assert!(fs.try_remove_dir_all(&dst.join("lib")).await.unwrap());
+        assert!(tokio_fs_ext::metadata(dst.join("extra.js")).await.is_err());
+        assert!(tokio_fs_ext::metadata(dst.join("lib")).await.is_err());
+        assert_eq!(
+            tokio_fs_ext::read(target.join("extra.js")).await.unwrap(),
+            b"x"
+        );
+        assert!(tokio_fs_ext::metadata(target.join("lib")).await.is_ok());
+
+        // Removing the package drops the link and cache entry; the shared
+        // store copy stays
+        assert!(fs.try_remove_dir_all(&dst).await.unwrap());
+        assert!(tokio_fs_ext::metadata(&target).await.is_ok());
+        assert!(
+            tokio_fs_ext::metadata(resolved_marker_path(&target))
+                .await
+                .is_ok()
+        );
+        assert!(tokio_fs_ext::metadata(&dst).await.is_err());
+        assert!(!fs.is_cached_link_path(&dst.join("index.js")));
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_create_fuse_links_rolls_back_on_failure() {
+        let base = Path::new("/test_fuse_links_batch");
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+        let old_target = base.join("stores/a-1.0.0");
+        let new_target = base.join("stores/a-2.0.0");
+        let existing = base.join("node_modules/a");
+        let fresh = base.join("node_modules/b");
+        let fs = FuseFs::new(100);
+
+        fs.create_fuse_link(&old_target, &existing).await.unwrap();
+        // A file where a directory is needed makes this link fail
+        tokio_fs_ext::write(base.join("blocked"), b"")
+            .await
+            .unwrap();
+        let blocked = base.join("blocked/node_modules/c");
+
+        let err = fs
+            .create_fuse_links(
+                &[
+                    (new_target.as_path(), existing.as_path()),
+                    (new_target.as_path(), fresh.as_path()),
+                    (new_target.as_path(), blocked.as_path()),
+                ],
+                1,
+            )
+            .await;
+        assert!(err.is_err());
+        let link = fs
+            .read_fuse_link(&existing.join("fuse.link"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(link.target_dir, old_target);
+        assert!(tokio_fs_ext::metadata(&fresh).await.is_err());
+
+        fs.create_fuse_links(
+            &[
+                (new_target.as_path(), existing.as_path()),
+                (new_target.as_path(), fresh.as_path()),
+            ],
+            4,
+        )
+        .await
+        .unwrap();
+        for dst in [&existing, &fresh] {
+            let link = fs
+                .read_fuse_link(&dst.join("fuse.link"))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(link.target_dir, new_target);
+        }
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_unlink() {
+        let base = Path::new("/test_fuse_unlink");
+        let dst = base.join("node_modules/pkg");
+        let target = base.join("stores/pkg-1.0.0");
+        let fs = FuseFs::new(100);
+
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        fs.create_fuse_link(&target, &dst).await.unwrap();
+
+        // Real files next to the link: refuse and leave everything in place
+        tokio_fs_ext::write(dst.join("stray.js"), b"x")
+            .await
+            .unwrap();
+        let err = fs.unlink(&dst).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DirectoryNotEmpty);
+        assert!(fs.is_cached_link_path(&dst.join("index.js")));
+        tokio_fs_ext::remove_file(dst.join("stray.js"))
+            .await
+            .unwrap();
+
+        fs.unlink(&dst).await.unwrap();
+        assert!(tokio_fs_ext::metadata(&dst).await.is_err());
+        assert!(!fs.is_cached_link_path(&dst.join("index.js")));
+        // The store copy is kept
+        assert!(tokio_fs_ext::metadata(&target).await.is_ok());
+
+        let err = fs.unlink(&dst).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_try_metadata() {
+        let base = Path::new("/test_fuse_metadata");
+        let dst = base.join("node_modules/pkg");
+        let target = base.join("stores/pkg-1.0.0");
+        let fs = FuseFs::new(100);
+
+        tokio_fs_ext::create_dir_all(target.join("lib"))
+            .await
+            .unwrap();
+        tokio_fs_ext::write(target.join("package.json"), b"{}")
+            .await
+            .unwrap();
+        fs.create_fuse_link(&target, &dst).await.unwrap();
+
+        // Files and directories resolve to the store
+        let file = fs
+            .try_metadata(&dst.join("package.json"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(file.is_file());
+        assert_eq!(file.len(), 2);
+        let dir = fs.try_metadata(&dst.join("lib")).await.unwrap().unwrap();
+        assert!(dir.is_dir());
+
+        // Missing in the store: fall through to the real filesystem
+        assert!(
+            fs.try_metadata(&dst.join("missing.js"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // No fuse link at all
+        assert!(
+            fs.try_metadata(&base.join("src/a.js"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_overlay_read_prefers_real_entry() {
+        let base = Path::new("/test_fuse_overlay_read");
+        let dst = base.join("node_modules/pkg");
+        let target = base.join("stores/pkg-1.0.0");
+        let fs = FuseFs::new(100);
+
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        tokio_fs_ext::write(target.join("index.js"), b"target")
+            .await
+            .unwrap();
+        fs.create_fuse_link(&target, &dst).await.unwrap();
+        // A real file next to fuse.link shadows the target's
+        tokio_fs_ext::write(dst.join("index.js"), b"real")
+            .await
+            .unwrap();
+
+        let index = dst.join("index.js");
+        let overlay = fs
+            .try_read_with_strategy(&index, ReadDirStrategy::Overlay)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&overlay[..], b"real");
+        let meta = fs.try_metadata(&index).await.unwrap().unwrap();
+        assert_eq!(meta.len(), 4);
+
+        let first = fs
+            .try_read_with_strategy(&index, ReadDirStrategy::FirstMatch)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&first[..], b"target");
+        let meta = fs
+            .try_metadata_with_strategy(&index, ReadDirStrategy::FirstMatch)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(meta.len(), 6);
+
+        // The listing agrees with the read
+        let entries = fs.try_read_dir(&dst).await.unwrap().unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .filter(|e| e.file_name() == "index.js")
+                .count(),
+            1
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_try_rename() {
+        let base = Path::new("/test_fuse_rename");
+        let from = base.join("node_modules/old");
+        let to = base.join("node_modules/new");
+        let target = base.join("stores/pkg-1.0.0");
+        let fs = FuseFs::new(100);
+
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        tokio_fs_ext::write(target.join("index.js"), b"ok")
+            .await
+            .unwrap();
+        fs.create_fuse_link(&target, &from).await.unwrap();
+        tokio_fs_ext::create_dir_all(from.join("node_modules/dep"))
+            .await
+            .unwrap();
+
+        assert!(fs.try_rename(&from, &to).await.unwrap());
+        assert_eq!(
+            fs.try_read(&to.join("index.js")).await.unwrap().as_deref(),
+            Some(&b"ok"[..])
+        );
+        assert!(tokio_fs_ext::metadata(&from).await.is_err());
+        assert!(
+            tokio_fs_ext::metadata(to.join("node_modules/dep"))
+                .await
+                .is_ok_and(|m| m.is_dir())
+        );
+
+        // Files inside the linked package live in the store
+        let err = fs
+            .try_rename(&to.join("index.js"), &to.join("main.js"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        // Real entries under a link are left to the caller
+        assert!(
+            !fs.try_rename(&to.join("node_modules/dep"), &to.join("node_modules/dep2"))
+                .await
+                .unwrap()
+        );
+
+        // Never over an existing entry
+        let other = base.join("node_modules/other");
+        tokio_fs_ext::create_dir_all(&other).await.unwrap();
+        let err = fs.try_rename(&to, &other).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert!(fs.try_read(&to.join("index.js")).await.unwrap().is_some());
+
+        // Plain directories are left to the caller
+        assert!(
+            !fs.try_rename(&base.join("src"), &base.join("lib"))
+                .await
+                .unwrap()
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    fn test_package_name_from_dir() {
+        assert_eq!(
+            package_name_from_dir(Path::new("/p/node_modules/lodash")),
+            Some("lodash".to_string())
+        );
+        assert_eq!(
+            package_name_from_dir(Path::new("/p/node_modules/a/node_modules/@s/b")),
+            Some("@s/b".to_string())
+        );
+        assert_eq!(package_name_from_dir(Path::new("/p/src/lib")), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_set_cache_max_entries_evicts_oldest() {
+        let fs = FuseFs::new(10);
+        for name in ["a", "b", "c"] {
+            let dst = PathBuf::from(format!("/p/node_modules/{name}"));
+            fs.warm_link_cache(&dst, Path::new("/stores/x"));
+        }
+        assert_eq!(
+            fs.cache_stats(),
+            FuseCacheStats {
+                entry_count: 3,
+                max_entries: 10,
+                hits: 0,
+                misses: 0,
+            }
+        );
+
+        fs.set_cache_max_entries(2);
+        assert_eq!(fs.cache_stats().entry_count, 2);
+        assert!(!fs.is_cached_link_path(Path::new("/p/node_modules/a/index.js")));
+        assert!(fs.is_cached_link_path(Path::new("/p/node_modules/c/index.js")));
+
+        fs.clear();
+        assert_eq!(fs.cache_stats().entry_count, 0);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_cache_hit_miss_counters() {
+        let base = Path::new("/test_fuse_cache_counters");
+        let dst = base.join("node_modules/pkg");
+        let target = base.join("stores/pkg-1.0.0");
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        tokio_fs_ext::write(target.join("index.js"), b"1")
+            .await
+            .unwrap();
+        FuseFs::new(10)
+            .create_fuse_link(&target, &dst)
+            .await
+            .unwrap();
+
+        // A fresh instance has to read the link from disk once
+        let fs = FuseFs::new(10);
+        assert_eq!(fs.cache_stats().hit_rate(), 0.0);
+        for _ in 0..4 {
+            fs.try_read(&dst.join("index.js")).await.unwrap().unwrap();
+        }
+        let stats = fs.cache_stats();
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+        assert_eq!(stats.hit_rate(), 0.75);
+
+        fs.clear();
+        assert_eq!(fs.cache_stats().hits + fs.cache_stats().misses, 0);
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_repair_broken_links() {
+        let base = Path::new("/test_repair_links");
+        let dst = base.join("project/node_modules/foo");
+        let old_target = base.join("old-stores/foo/-/foo-1.0.0");
+        let store_root = base.join("stores");
+        let new_target = store_root.join("foo/-/foo-1.0.0");
+
+        let fs = FuseFs::new(100);
+        fs.create_fuse_link(&old_target, &dst).await.unwrap();
+
+        // No store copy yet — nothing to repair
+        let repaired = fs
+            .repair_broken_links(&base.join("project"), &store_root)
+            .await
+            .unwrap();
+        assert_eq!(repaired, 0);
+
+        tokio_fs_ext::create_dir_all(&new_target).await.unwrap();
+        let sentinel = PathBuf::from(format!("{}._resolved", new_target.display()));
+        tokio_fs_ext::write(&sentinel, b"").await.unwrap();
+
+        let repaired = fs
+            .repair_broken_links(&base.join("project"), &store_root)
+            .await
+            .unwrap();
+        assert_eq!(repaired, 1);
+        let content = tokio_fs_ext::read_to_string(&dst.join("fuse.link"))
+            .await
+            .unwrap();
+        assert_eq!(FuseLink::parse(&content).unwrap().target_dir, new_target);
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_cached_and_scanned_links() {
+        let base = Path::new("/test_dump_links");
+        let root = base.join("project");
+        let store_link = root.join("node_modules/foo");
+        let dev_link = root.join("node_modules/@scope/bar");
+        let foo_target = base.join("stores/foo/-/foo-1.0.0");
+        let bar_target = base.join("packages/bar");
+
+        let fs = FuseFs::new(100);
+        fs.create_fuse_link(&foo_target, &store_link).await.unwrap();
+        fs.create_dev_link(&bar_target, &dev_link).await.unwrap();
+
+        let expected = vec![
+            FuseLinkInfo {
+                link_file: dev_link.join("fuse.link"),
+                target_dir: bar_target,
+                dev: true,
+            },
+            FuseLinkInfo {
+                link_file: store_link.join("fuse.link"),
+                target_dir: foo_target,
+                dev: false,
+            },
+        ];
+        assert_eq!(fs.cached_links(), expected);
+
+        // A fresh instance has nothing cached but finds both on disk
+        let fresh = FuseFs::new(100);
+        assert!(fresh.cached_links().is_empty());
+        assert_eq!(fresh.scan_links(&root).await.unwrap(), expected);
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_evict_stale_links() {
+        let base = Path::new("/test_evict_stale_links");
+        let (a, b) = (base.join("node_modules/a"), base.join("node_modules/b"));
+        let fs = FuseFs::new(100);
+        fs.create_fuse_link(&base.join("stores/a-1.0.0"), &a)
+            .await
+            .unwrap();
+        fs.create_fuse_link(&base.join("stores/b-1.0.0"), &b)
+            .await
+            .unwrap();
+        assert_eq!(fs.evict_stale_links().await, 0);
+
+        // Another context upgrades `a` and removes `b` behind our back
+        FuseFs::new(100)
+            .create_fuse_link(&base.join("stores/a-2.0.0"), &a)
+            .await
+            .unwrap();
+        tokio_fs_ext::remove_file(b.join("fuse.link"))
+            .await
+            .unwrap();
+
+        assert_eq!(fs.evict_stale_links().await, 2);
+        assert!(fs.cached_links().is_empty());
+        assert_eq!(
+            fs.try_real_path(&a.join("index.js")).await.unwrap(),
+            Some(base.join("stores/a-2.0.0/index.js"))
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
     fn cached_target(fs: &FuseFs, path: &Path) -> Option<PathBuf> {
         fs.link_cache
             .read()
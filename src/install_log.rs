@@ -0,0 +1,75 @@
+//! Install telemetry — one JSON object per install session, appended as a
+//! line to `.opfs-project-install.log` (JSONL) in the project CWD.
+
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::OpfsError;
+
+/// File name of the install log, relative to the CWD.
+pub const INSTALL_LOG_FILE: &str = ".opfs-project-install.log";
+
+/// A freshly downloaded package recorded in an [`InstallLogEntry`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadedPackage {
+    pub name: String,
+    pub version: String,
+    /// Size of the downloaded tgz in bytes
+    pub bytes: u64,
+    /// Time spent downloading and verifying the tgz
+    pub duration_ms: u64,
+}
+
+/// One install session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallLogEntry {
+    /// Unix time (ms) at which the install started
+    pub timestamp_ms: u64,
+    /// Wall-clock duration of the whole install
+    pub duration_ms: u64,
+    /// Packages downloaded during this session
+    pub installed: Vec<DownloadedPackage>,
+    /// `name@version` of packages already present in the store
+    pub cached: Vec<String>,
+    /// Sum of `installed[].bytes`
+    pub total_bytes_downloaded: u64,
+    /// Error messages for packages that failed to install
+    pub errors: Vec<String>,
+}
+
+/// Append `entry` as a single line to the log at `path`.
+pub(crate) async fn append(path: &Path, entry: &InstallLogEntry) -> Result<(), OpfsError> {
+    let line = serde_json::to_string(entry)
+        .map_err(|e| OpfsError::Other(format!("serialize install log: {e}")))?;
+
+    let mut content = match tokio_fs_ext::read(path).await {
+        Ok(c) => c,
+        Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    content.extend_from_slice(line.as_bytes());
+    content.push(b'\n');
+
+    tokio_fs_ext::write(path, &content).await?;
+    Ok(())
+}
+
+/// Read every entry from the log at `path`. A missing log reads as empty.
+pub(crate) async fn read(path: &Path) -> Result<Vec<InstallLogEntry>, OpfsError> {
+    let content = match tokio_fs_ext::read_to_string(path).await {
+        Ok(c) => c,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| OpfsError::Other(format!("parse install log: {e}")))
+        })
+        .collect()
+}
@@ -18,17 +18,27 @@ pub mod archive;
 pub mod config;
 pub mod error;
 pub mod fuse_fs;
+pub mod install_log;
 pub mod package_lock;
 pub mod package_manager;
+pub mod pnpm_lock;
 pub mod project;
+pub mod registry;
 pub mod store;
+pub mod yarn_lock;
 
 // ── re-exports ───────────────────────────────────────────────────────────
 
 pub use config::Config;
 pub use error::{OpfsError, VerifyResult};
-pub use package_manager::{InstallOptions, OmitType};
+pub use fuse_fs::{FuseCacheStats, FuseLinkInfo, FuseLinkOptions, ReadDirStrategy};
+pub use install_log::InstallLogEntry;
+pub use package_manager::{
+    InstallCheck, InstallOptions, InstallProgress, InstallReport, OmitType, ProgressCallback,
+};
 pub use project::OpfsProject;
+pub use registry::RegistryConfig;
+pub use store::{InstalledPackage, PackageStorePaths, PruneOptions, PruneReport};
 
 // ── test utilities ───────────────────────────────────────────────────────
 
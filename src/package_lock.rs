@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Represents package information in package-lock.json
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -16,6 +16,8 @@ pub struct LockPackage {
     pub dev_dependencies: Option<HashMap<String, String>>,
     #[serde(rename = "peerDependencies")]
     pub peer_dependencies: Option<HashMap<String, String>>,
+    #[serde(rename = "peerDependenciesMeta")]
+    pub peer_dependencies_meta: Option<HashMap<String, PeerDependencyMeta>>,
     #[serde(rename = "optionalDependencies")]
     pub optional_dependencies: Option<HashMap<String, String>>,
     pub requires: Option<HashMap<String, String>>,
@@ -30,6 +32,35 @@ pub struct LockPackage {
     pub os: Option<serde_json::Value>,
     /// CPU constraints (e.g., ["arm64", "x64"])
     pub cpu: Option<serde_json::Value>,
+    /// Tarball size in bytes, when known from registry metadata
+    pub size: Option<u64>,
+    /// Dependencies shipped inside this package's tarball under its own
+    /// `node_modules/`
+    #[serde(rename = "bundleDependencies", alias = "bundledDependencies")]
+    pub bundled_dependencies: Option<BundledDependencies>,
+    /// Shipped inside the tarball of an ancestor package, as npm marks
+    /// bundled dependencies and everything they depend on
+    #[serde(rename = "inBundle")]
+    pub in_bundle: Option<bool>,
+    /// Symlink to a local directory, e.g. a workspace package; `resolved`
+    /// then holds its path relative to the lockfile
+    pub link: Option<bool>,
+}
+
+/// Per-peer settings of `peerDependenciesMeta`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerDependencyMeta {
+    /// The package works without this peer
+    pub optional: Option<bool>,
+}
+
+/// `bundleDependencies` of a package: `true` bundles every dependency,
+/// a list only the named ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BundledDependencies {
+    Bool(bool),
+    List(Vec<String>),
 }
 
 impl LockPackage {
@@ -62,24 +93,862 @@ impl LockPackage {
             None => Cow::Borrowed("unknown"),
         }
     }
+
+    /// Whether the package is only reachable through optional dependencies.
+    pub fn is_optional(&self) -> bool {
+        self.optional.unwrap_or(false)
+    }
+
+    /// Whether the package at `install_path` is an npm alias, e.g.
+    /// `"react": "npm:preact@10"` installs `preact` at
+    /// `node_modules/react`.
+    ///
+    /// The store is keyed by the real name ([`get_name`](Self::get_name));
+    /// the fuse link and `require` use the alias ([`install_name`]).
+    pub fn is_alias(&self, install_path: &str) -> bool {
+        self.name
+            .as_deref()
+            .is_some_and(|name| name != install_name(install_path))
+    }
+
+    /// Whether `name` ships inside this package's tarball.
+    pub fn bundles(&self, name: &str) -> bool {
+        match &self.bundled_dependencies {
+            Some(BundledDependencies::Bool(all)) => {
+                *all && [&self.dependencies, &self.optional_dependencies]
+                    .into_iter()
+                    .flatten()
+                    .any(|deps| deps.contains_key(name))
+            }
+            Some(BundledDependencies::List(names)) => names.iter().any(|b| b == name),
+            None => false,
+        }
+    }
+
+    /// Whether peer dependency `name` is marked optional in
+    /// `peerDependenciesMeta`.
+    pub fn is_optional_peer(&self, name: &str) -> bool {
+        self.peer_dependencies_meta
+            .as_ref()
+            .and_then(|meta| meta.get(name))
+            .and_then(|meta| meta.optional)
+            .unwrap_or(false)
+    }
+
+    /// Whether the package ships inside an ancestor's tarball (`inBundle`).
+    pub fn is_in_bundle(&self) -> bool {
+        self.in_bundle.unwrap_or(false)
+    }
+
+    /// Whether the package is only reachable through dev dependencies.
+    pub fn is_dev(&self) -> bool {
+        self.dev.unwrap_or(false)
+    }
+
+    /// Executables of the package, as `bin name → path in the package`.
+    ///
+    /// Normalizes both forms of `bin`: a string (one executable named after
+    /// the package, without its scope) and an object. Leading `./` is
+    /// removed from paths; names that are not a single path component
+    /// (empty, `.`, `..`, or containing a separator) are dropped, since they
+    /// are joined onto `node_modules/.bin`.
+    pub fn get_bin_entries(&self, package_name: &str) -> HashMap<String, String> {
+        let normalize = |path: &str| path.trim_start_matches("./").to_string();
+        let valid = |name: &str| !matches!(name, "" | "." | "..") && !name.contains(['/', '\\']);
+        match &self.bin {
+            Some(serde_json::Value::String(path)) => {
+                let name = package_name.rsplit('/').next().unwrap_or(package_name);
+                if !valid(name) {
+                    return HashMap::new();
+                }
+                HashMap::from([(name.to_string(), normalize(path))])
+            }
+            Some(serde_json::Value::Object(bins)) => bins
+                .iter()
+                .filter(|(name, _)| valid(name.as_str()))
+                .filter_map(|(name, path)| Some((name.clone(), normalize(path.as_str()?))))
+                .collect(),
+            _ => HashMap::new(),
+        }
+    }
+
+    /// Directory of a local dependency (`file:` or workspace link),
+    /// relative to the lockfile. `None` for packages fetched over HTTP.
+    pub fn local_path(&self) -> Option<&str> {
+        let resolved = self.resolved.as_deref()?;
+        match resolved.strip_prefix("file:") {
+            Some(path) => Some(path),
+            None => self.link.unwrap_or(false).then_some(resolved),
+        }
+    }
 }
 
 /// Represents complete package-lock.json file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageLock {
+    /// Root package name; empty when the lockfile omits it (some v3 files do)
+    #[serde(default)]
     pub name: String,
+    /// Root package version; empty when the lockfile omits it
+    #[serde(default)]
     pub version: String,
     #[serde(rename = "lockfileVersion")]
     pub lockfile_version: u32,
+    #[serde(default)]
     pub requires: bool,
     pub packages: HashMap<String, LockPackage>,
 
     pub dependencies: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Newest `lockfileVersion` this crate understands.
+const MAX_LOCKFILE_VERSION: u32 = 3;
+
+/// Rough ratio of unpacked size to tgz size for npm packages.
+const TGZ_COMPRESSION_RATIO: u64 = 3;
+
 impl PackageLock {
     /// Parse from json string
+    ///
+    /// Lockfile versions 1–3 are supported; newer versions are parsed on a
+    /// best-effort basis with a warning (see
+    /// [`lockfile_version_compat`](Self::lockfile_version_compat)).
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+        let lock: Self = serde_json::from_str(json)?;
+        lock.lockfile_version_compat();
+        Ok(lock)
+    }
+
+    /// Like [`from_json`](Self::from_json), for raw file contents.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        let lock: Self = serde_json::from_slice(bytes)?;
+        lock.lockfile_version_compat();
+        Ok(lock)
+    }
+
+    /// Like [`from_json`](Self::from_json), parsing straight from `reader`
+    /// (e.g. a `GzDecoder`) without first buffering the whole file.
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
+        let lock: Self = serde_json::from_reader(reader)?;
+        lock.lockfile_version_compat();
+        Ok(lock)
+    }
+
+    /// Whether `lockfileVersion` is one this crate understands; logs a
+    /// warning if it is newer.
+    pub fn lockfile_version_compat(&self) -> bool {
+        if self.lockfile_version > MAX_LOCKFILE_VERSION {
+            tracing::warn!(
+                "lockfileVersion {} is newer than the supported {MAX_LOCKFILE_VERSION}; \
+                 installing on a best-effort basis",
+                self.lockfile_version
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Estimate the unpacked size of all packages, in bytes.
+    ///
+    /// Uses the `size` hint of each package (tgz size) times a typical
+    /// compression ratio. Packages without a hint are not counted, so
+    /// the result is a lower bound.
+    pub fn total_install_size_estimate(&self) -> u64 {
+        self.packages
+            .iter()
+            .filter(|(path, _)| !path.is_empty())
+            .filter_map(|(_, pkg)| pkg.size)
+            .map(|size| size.saturating_mul(TGZ_COMPRESSION_RATIO))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Estimate the bytes an install downloads, from the `size` hint of
+    /// each tarball.
+    ///
+    /// A tarball shared by several install paths counts once; bundled and
+    /// local packages are skipped since nothing is fetched for them.
+    /// Packages without a hint are not counted, so the result is a lower
+    /// bound; `OpfsProject::estimate_download_size` asks the registry.
+    pub fn total_download_size(&self) -> u64 {
+        let mut seen = HashSet::new();
+        self.packages
+            .iter()
+            .filter(|(path, pkg)| {
+                !path.is_empty()
+                    && pkg.local_path().is_none()
+                    && self.bundling_parent(path).is_none()
+            })
+            .filter(|(path, pkg)| seen.insert(pkg.resolved.as_deref().unwrap_or(path.as_str())))
+            .filter_map(|(_, pkg)| pkg.size)
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Lock path of the package whose `node_modules` directly contains the
+    /// bundled package at `path`, if it is bundled: marked `inBundle`, or
+    /// listed in that parent's `bundleDependencies` (lockfiles without
+    /// `inBundle`).
+    ///
+    /// The parent is either the package whose tarball ships it or, for a
+    /// dependency of a bundled package, itself bundled; following parents
+    /// up to the first that is not bundled finds the tarball.
+    pub fn bundling_parent<'a>(&self, path: &'a str) -> Option<&'a str> {
+        let parent = &path[..path.rfind("/node_modules/")?];
+        let in_bundle = self
+            .packages
+            .get(path)
+            .is_some_and(LockPackage::is_in_bundle);
+        self.packages
+            .get(parent)
+            .is_some_and(|pkg| in_bundle || pkg.bundles(install_name(path)))
+            .then_some(parent)
+    }
+
+    /// Which installed copy of `name` a `require(name)` from the package at
+    /// `from_path` (e.g. `node_modules/a`, or `""` for the project root)
+    /// loads, following Node's resolution: `<from_path>/node_modules/<name>`,
+    /// then the same under each ancestor package, up to the root.
+    ///
+    /// Returns the lock path of the match, e.g. `node_modules/react`.
+    pub fn resolve_package_path(&self, name: &str, from_path: &str) -> Option<String> {
+        self.resolve_key(name, from_path).map(str::to_string)
+    }
+
+    /// Lock paths of the packages the one at `install_path` loads at
+    /// runtime: its `dependencies`, `optionalDependencies` and
+    /// `peerDependencies`, resolved like
+    /// [`resolve_package_path`](Self::resolve_package_path). For the root
+    /// (`""`) `devDependencies` count too, since they are installed.
+    ///
+    /// Dependencies missing from the lock (e.g. omitted optional packages)
+    /// are left out. The result is sorted and free of duplicates.
+    pub fn dependencies_of<'a>(&'a self, install_path: &str) -> Vec<&'a str> {
+        let Some(pkg) = self.packages.get(install_path) else {
+            return Vec::new();
+        };
+        let dev = install_path.is_empty().then_some(&pkg.dev_dependencies);
+        let mut paths: Vec<&str> = [
+            &pkg.dependencies,
+            &pkg.optional_dependencies,
+            &pkg.peer_dependencies,
+        ]
+        .into_iter()
+        .chain(dev)
+        .flatten()
+        .flat_map(|deps| deps.keys())
+        .filter_map(|name| self.resolve_key(name, install_path))
+        .collect();
+        paths.sort_unstable();
+        paths.dedup();
+        paths
+    }
+
+    /// The `packages` key `require(name)` from `from_path` resolves to.
+    fn resolve_key(&self, name: &str, from_path: &str) -> Option<&str> {
+        let mut base = from_path.trim_end_matches('/');
+        loop {
+            let candidate = join_node_modules(base, name);
+            if let Some((key, _)) = self.packages.get_key_value(&candidate) {
+                return Some(key);
+            }
+            if base.is_empty() {
+                return None;
+            }
+            base = base.rfind("/node_modules/").map_or("", |i| &base[..i]);
+        }
+    }
+}
+
+/// A direct dependency of the root project, as listed in its manifest or
+/// lockfile.
+pub(crate) struct RootDependency<'a> {
+    pub alias: &'a str,
+    pub reference: &'a str,
+    pub dev: bool,
+    pub optional: bool,
+}
+
+/// A package resolved from a dependency edge of a non-npm lockfile.
+pub(crate) struct ResolvedPackage<'a> {
+    /// Identity of the package (e.g. `name@version`); two edges resolving
+    /// to the same key can share an install path.
+    pub key: String,
+    /// Lock entry without `dev`/`optional` flags; layout fills those in.
+    pub package: LockPackage,
+    /// Dependency edges as (alias, reference)
+    pub dependencies: Vec<(&'a str, &'a str)>,
+}
+
+/// A dependency edge `alias: reference` from the package at `parent`
+/// (`""` for the root).
+struct Edge<'a> {
+    parent: String,
+    alias: &'a str,
+    reference: &'a str,
+    dev: bool,
+    optional: bool,
+}
+
+/// `<base>/node_modules/<name>`, or `node_modules/<name>` at the root.
+fn join_node_modules(base: &str, name: &str) -> String {
+    if base.is_empty() {
+        format!("node_modules/{name}")
+    } else {
+        format!("{base}/node_modules/{name}")
+    }
+}
+
+impl PackageLock {
+    /// Lay out a dependency graph as an npm-style `node_modules` tree.
+    ///
+    /// Direct dependencies claim the top level first. Transitive
+    /// dependencies are hoisted to the top level when free and nested under
+    /// their dependent otherwise; production edges are laid out before
+    /// dev-only ones so that the latter get nested on conflict.
+    ///
+    /// A package is `dev` (or `optional`) only if every edge reaching it
+    /// is; e.g. a dev root that production code also depends on is
+    /// installed, with its dependencies, as a production package.
+    pub(crate) fn from_dependency_graph<'a>(
+        roots: &[RootDependency<'a>],
+        mut resolve: impl FnMut(&'a str, &'a str) -> Option<ResolvedPackage<'a>>,
+    ) -> Self {
+        let mut packages: HashMap<String, LockPackage> = HashMap::new();
+        // install path → package key, used to detect conflicts
+        let mut placed: HashMap<String, String> = HashMap::new();
+        let mut prod_queue = VecDeque::new();
+        let mut dev_queue = VecDeque::new();
+
+        let mut pending: VecDeque<Edge<'a>> = roots
+            .iter()
+            .map(|root| Edge {
+                parent: String::new(),
+                alias: root.alias,
+                reference: root.reference,
+                dev: root.dev,
+                optional: root.optional,
+            })
+            .collect();
+
+        loop {
+            let edge = match pending.pop_front() {
+                Some(edge) => edge,
+                None => match prod_queue.pop_front().or_else(|| dev_queue.pop_front()) {
+                    Some(edge) => edge,
+                    None => break,
+                },
+            };
+            let Some(resolved) = resolve(edge.alias, edge.reference) else {
+                tracing::warn!("lockfile: cannot resolve {}@{}", edge.alias, edge.reference);
+                continue;
+            };
+
+            // Node resolution from the parent: the nearest
+            // `node_modules/<alias>` wins, so reuse it if it is the same
+            // package, otherwise nest.
+            let mut base = edge.parent.as_str();
+            let path = loop {
+                let candidate = join_node_modules(base, edge.alias);
+                match placed.get(&candidate) {
+                    Some(existing) if *existing == resolved.key => break candidate,
+                    Some(_) => break join_node_modules(&edge.parent, edge.alias),
+                    None if base.is_empty() => break candidate,
+                    None => base = base.rfind("/node_modules/").map_or("", |i| &base[..i]),
+                }
+            };
+
+            let optional = edge.optional || resolved.package.optional == Some(true);
+            let (dev, optional) = match placed.get(&path).map(|key| *key == resolved.key) {
+                // Another package already holds the nested path
+                Some(false) => continue,
+                // Already laid out, possibly by a dev or optional edge: a
+                // production (or required) edge clears the flag, and its
+                // dependencies are laid out again with the cleared flags.
+                Some(true) => {
+                    let Some(package) = packages.get_mut(&path) else {
+                        continue;
+                    };
+                    let was_dev = package.dev == Some(true);
+                    let was_optional = package.optional == Some(true);
+                    let (dev, optional) = (was_dev && edge.dev, was_optional && optional);
+                    if (dev, optional) == (was_dev, was_optional) {
+                        continue;
+                    }
+                    package.dev = dev.then_some(true);
+                    package.optional = optional.then_some(true);
+                    (dev, optional)
+                }
+                None => {
+                    placed.insert(path.clone(), resolved.key);
+                    let mut package = resolved.package;
+                    package.dev = edge.dev.then_some(true);
+                    package.optional = optional.then_some(true);
+                    packages.insert(path.clone(), package);
+                    (edge.dev, optional)
+                }
+            };
+
+            let queue = if dev { &mut dev_queue } else { &mut prod_queue };
+            queue.extend(
+                resolved
+                    .dependencies
+                    .into_iter()
+                    .map(|(alias, reference)| Edge {
+                        parent: path.clone(),
+                        alias,
+                        reference,
+                        dev,
+                        optional,
+                    }),
+            );
+        }
+
+        let root_deps = |dev: bool| {
+            let deps: HashMap<String, String> = roots
+                .iter()
+                .filter(|root| root.dev == dev)
+                .map(|root| (root.alias.to_string(), root.reference.to_string()))
+                .collect();
+            (!deps.is_empty()).then_some(deps)
+        };
+        packages.insert(
+            String::new(),
+            LockPackage {
+                dependencies: root_deps(false),
+                dev_dependencies: root_deps(true),
+                ..Default::default()
+            },
+        );
+
+        PackageLock {
+            name: String::new(),
+            version: String::new(),
+            lockfile_version: 3,
+            requires: true,
+            packages,
+            dependencies: None,
+        }
+    }
+}
+
+/// Name a package is installed and `require`d under: the part of its lock
+/// path after the last `node_modules/`, e.g. `node_modules/a/node_modules/@s/b`
+/// → `@s/b`. Differs from the package name for aliases; see
+/// [`LockPackage::is_alias`].
+pub fn install_name(path: &str) -> &str {
+    path.rsplit_once("node_modules/")
+        .map_or(path, |(_, name)| name)
+}
+
+/// Whether `name` has the shape of a package name, `name` or
+/// `@scope/name`, and is therefore safe to join onto a directory: no empty,
+/// `.` or `..` components and no other separators.
+pub fn is_valid_package_name(name: &str) -> bool {
+    let valid = |component: &str| {
+        !component.is_empty()
+            && component != "."
+            && component != ".."
+            && !component.contains(['/', '\\'])
+    };
+    match name.strip_prefix('@') {
+        Some(scoped) => scoped
+            .split_once('/')
+            .is_some_and(|(scope, name)| valid(scope) && valid(name)),
+        None => valid(name),
+    }
+}
+
+/// Lockfile formats recognised by file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockfileFormat {
+    /// `package-lock.json`
+    Npm,
+    /// `yarn.lock`
+    Yarn,
+    /// `pnpm-lock.yaml`
+    Pnpm,
+}
+
+impl LockfileFormat {
+    /// All formats, in detection priority order.
+    pub const ALL: [Self; 3] = [Self::Npm, Self::Yarn, Self::Pnpm];
+
+    /// File name of this lockfile format.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Npm => "package-lock.json",
+            Self::Yarn => "yarn.lock",
+            Self::Pnpm => "pnpm-lock.yaml",
+        }
+    }
+
+    /// The format whose file name `path` ends in, if any.
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        let file_name = path.file_name()?;
+        Self::ALL
+            .into_iter()
+            .find(|format| file_name == format.file_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_total_install_size_estimate() {
+        let lock = PackageLock::from_json(
+            r#"{
+                "name": "app",
+                "version": "1.0.0",
+                "lockfileVersion": 3,
+                "requires": true,
+                "packages": {
+                    "": { "name": "app", "size": 1000 },
+                    "node_modules/a": { "version": "1.0.0", "size": 100 },
+                    "node_modules/b": { "version": "2.0.0", "size": 50 },
+                    "node_modules/c": { "version": "3.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(lock.total_install_size_estimate(), 450);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_resolve_package_path() {
+        let lock = PackageLock::from_json(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {},
+                    "node_modules/a": { "version": "1.0.0" },
+                    "node_modules/b": { "version": "1.0.0" },
+                    "node_modules/react": { "version": "18.2.0" },
+                    "node_modules/b/node_modules/react": { "version": "17.0.2" },
+                    "node_modules/b/node_modules/c": { "version": "1.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let resolve = |name, from| lock.resolve_package_path(name, from);
+
+        // Deduplicated: `a` shares the top-level copy
+        assert_eq!(
+            resolve("react", "node_modules/a").as_deref(),
+            Some("node_modules/react")
+        );
+        assert_eq!(resolve("react", "").as_deref(), Some("node_modules/react"));
+        // Shadowed: `b` and everything under it get the nested copy
+        assert_eq!(
+            resolve("react", "node_modules/b").as_deref(),
+            Some("node_modules/b/node_modules/react")
+        );
+        assert_eq!(
+            resolve("react", "node_modules/b/node_modules/c").as_deref(),
+            Some("node_modules/b/node_modules/react")
+        );
+        // Nested packages are invisible from outside their parent
+        assert_eq!(resolve("c", "node_modules/a"), None);
+        assert_eq!(resolve("missing", "node_modules/a"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_is_alias() {
+        let preact = LockPackage {
+            name: Some("preact".to_string()),
+            ..Default::default()
+        };
+        assert!(preact.is_alias("node_modules/react"));
+        assert!(!preact.is_alias("node_modules/a/node_modules/preact"));
+        assert!(!LockPackage::default().is_alias("node_modules/react"));
+
+        let scoped = LockPackage {
+            name: Some("@s/b".to_string()),
+            ..Default::default()
+        };
+        assert!(!scoped.is_alias("node_modules/a/node_modules/@s/b"));
+        assert_eq!(install_name("node_modules/a/node_modules/@s/b"), "@s/b");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_get_bin_entries() {
+        let with_bin = |bin: serde_json::Value| LockPackage {
+            bin: Some(bin),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            with_bin(serde_json::json!("./bin/cli.js")).get_bin_entries("@scope/tool"),
+            HashMap::from([("tool".to_string(), "bin/cli.js".to_string())])
+        );
+        assert_eq!(
+            with_bin(serde_json::json!({
+                "tsc": "bin/tsc",
+                "tsserver": "./bin/tsserver",
+                "../evil": "x.js",
+                ".": "dot.js",
+                "..": "dotdot.js",
+                "bad": 1
+            }))
+            .get_bin_entries("typescript"),
+            HashMap::from([
+                ("tsc".to_string(), "bin/tsc".to_string()),
+                ("tsserver".to_string(), "bin/tsserver".to_string()),
+            ])
+        );
+        assert!(
+            with_bin(serde_json::json!("cli.js"))
+                .get_bin_entries("@scope/..")
+                .is_empty()
+        );
+        assert!(LockPackage::default().get_bin_entries("a").is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_total_download_size() {
+        let lock = PackageLock::from_json(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app" },
+                    "node_modules/a": {
+                        "version": "1.0.0",
+                        "resolved": "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+                        "size": 100,
+                        "bundleDependencies": ["c"]
+                    },
+                    "node_modules/b/node_modules/a": {
+                        "version": "1.0.0",
+                        "resolved": "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+                        "size": 100
+                    },
+                    "node_modules/a/node_modules/c": { "version": "1.0.0", "size": 30 },
+                    "node_modules/b": {
+                        "version": "1.0.0",
+                        "resolved": "https://registry.npmjs.org/b/-/b-1.0.0.tgz",
+                        "size": 50
+                    },
+                    "node_modules/local": { "resolved": "file:local", "size": 10 }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(lock.total_download_size(), 150);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_bundling_parent() {
+        let lock = PackageLock::from_json(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app" },
+                    "node_modules/a": {
+                        "version": "1.0.0",
+                        "dependencies": { "b": "^1.0.0" },
+                        "bundleDependencies": true
+                    },
+                    "node_modules/a/node_modules/b": {
+                        "version": "1.0.0",
+                        "dependencies": { "c": "^1.0.0" },
+                        "inBundle": true
+                    },
+                    "node_modules/a/node_modules/b/node_modules/c": {
+                        "version": "1.0.0",
+                        "inBundle": true
+                    },
+                    "node_modules/d": {
+                        "version": "1.0.0",
+                        "bundleDependencies": ["e"]
+                    },
+                    "node_modules/d/node_modules/e": { "version": "1.0.0" },
+                    "node_modules/d/node_modules/f": { "version": "1.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            lock.packages["node_modules/a"].bundled_dependencies,
+            Some(BundledDependencies::Bool(true))
+        );
+        assert!(lock.packages["node_modules/a"].bundles("b"));
+        assert!(!lock.packages["node_modules/a"].bundles("x"));
+
+        assert_eq!(
+            lock.bundling_parent("node_modules/a/node_modules/b"),
+            Some("node_modules/a")
+        );
+        // A dependency of a bundled package, not listed by its parent
+        assert_eq!(
+            lock.bundling_parent("node_modules/a/node_modules/b/node_modules/c"),
+            Some("node_modules/a/node_modules/b")
+        );
+        // No `inBundle`: the parent's list decides
+        assert_eq!(
+            lock.bundling_parent("node_modules/d/node_modules/e"),
+            Some("node_modules/d")
+        );
+        assert_eq!(lock.bundling_parent("node_modules/d/node_modules/f"), None);
+        assert_eq!(lock.bundling_parent("node_modules/a"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_dependencies_of() {
+        let lock = PackageLock::from_json(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {
+                        "dependencies": { "a": "^1.0.0" },
+                        "devDependencies": { "b": "^1.0.0" }
+                    },
+                    "node_modules/a": {
+                        "version": "1.0.0",
+                        "dependencies": { "react": "^17.0.0", "b": "^1.0.0" },
+                        "optionalDependencies": { "fsevents": "^2.0.0" },
+                        "peerDependencies": { "b": "^1.0.0" },
+                        "devDependencies": { "jest": "^29.0.0" }
+                    },
+                    "node_modules/a/node_modules/react": { "version": "17.0.2" },
+                    "node_modules/b": { "version": "1.0.0", "dev": true },
+                    "node_modules/react": { "version": "18.2.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lock.dependencies_of(""),
+            ["node_modules/a", "node_modules/b"]
+        );
+        // Nested copy wins; dev dependencies and missing optionals are skipped
+        assert_eq!(
+            lock.dependencies_of("node_modules/a"),
+            ["node_modules/a/node_modules/react", "node_modules/b"]
+        );
+        assert!(lock.dependencies_of("node_modules/react").is_empty());
+        assert!(lock.dependencies_of("node_modules/missing").is_empty());
+    }
+
+    /// npm 9 `package-lock.json` without top-level `name`, `version` or
+    /// `requires`, and without the legacy `dependencies` tree.
+    const V3_LOCKFILE: &str = r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": {
+      "dependencies": {
+        "@babel/runtime": "^7.23.0",
+        "debug": "^4.3.4"
+      },
+      "devDependencies": {
+        "typescript": "^5.2.2"
+      }
+    },
+    "node_modules/@babel/runtime": {
+      "version": "7.23.2",
+      "resolved": "https://registry.npmjs.org/@babel/runtime/-/runtime-7.23.2.tgz",
+      "integrity": "sha512-mM8eg4yl5D6i3lu2QKPuPH4FArvJ8KhTofbE7jwMUv9KX5mBvwPAqnV3MlyBNqdp9RyRKP6Yck8TrfYrPvX3bg==",
+      "dependencies": {
+        "regenerator-runtime": "^0.14.0"
+      },
+      "engines": {
+        "node": ">=6.9.0"
+      }
+    },
+    "node_modules/debug": {
+      "version": "4.3.4",
+      "resolved": "https://registry.npmjs.org/debug/-/debug-4.3.4.tgz",
+      "integrity": "sha512-PRWFHuSU3eDtQJPvnNY7Jcket1j0t5OuOsFzPPzsekD52Zl8qUfFIPEiswXqIvHWGVHOgX+7G/vCNNhehwxfkQ==",
+      "dependencies": {
+        "ms": "2.1.2"
+      },
+      "engines": {
+        "node": ">=6.0"
+      },
+      "peerDependenciesMeta": {
+        "supports-color": {
+          "optional": true
+        }
+      }
+    },
+    "node_modules/ms": {
+      "version": "2.1.2",
+      "resolved": "https://registry.npmjs.org/ms/-/ms-2.1.2.tgz",
+      "integrity": "sha512-sGkPx+VjMtmA6MX27oA4FBFELFCZZ4S4XqeGOXCv68tT+jb3vk/RyaKWP0PTKyWtmLSM0b+adUTEvbs1PEaH2w=="
+    },
+    "node_modules/regenerator-runtime": {
+      "version": "0.14.0",
+      "resolved": "https://registry.npmjs.org/regenerator-runtime/-/regenerator-runtime-0.14.0.tgz",
+      "integrity": "sha512-srw17NI0TUWHuGa5CFGGmhfNIeja30WMBfbslPNhf6JrqQlLN5gcrvig1oqPxiVaXb0oW0XRKtH6Nngs5lKCIA=="
+    },
+    "node_modules/typescript": {
+      "version": "5.2.2",
+      "resolved": "https://registry.npmjs.org/typescript/-/typescript-5.2.2.tgz",
+      "integrity": "sha512-mI4WrpHsbCIcwT9cF4FZvr80QUeKvsUsUvKDoR+X/7XHQH98xYD8YHZg7ANtz2GtZt/CBq2QJ0thkGJMHfqc1w==",
+      "dev": true,
+      "bin": {
+        "tsc": "bin/tsc",
+        "tsserver": "bin/tsserver"
+      },
+      "engines": {
+        "node": ">=14.17"
+      }
+    }
+  }
+}"#;
+
+    #[wasm_bindgen_test]
+    fn test_from_bytes_and_reader() {
+        use std::io::Write;
+
+        let json = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "app" },
+                "node_modules/a": { "version": "1.0.0" }
+            }
+        }"#;
+        let check = |lock: PackageLock| {
+            assert_eq!(lock.packages.len(), 2);
+            assert_eq!(
+                lock.packages["node_modules/a"].version.as_deref(),
+                Some("1.0.0")
+            );
+        };
+
+        check(PackageLock::from_bytes(json.as_bytes()).unwrap());
+
+        // Gzipped lockfile, decompressed while parsing
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+        let gz = encoder.finish().unwrap();
+        check(PackageLock::from_reader(flate2::read::GzDecoder::new(gz.as_slice())).unwrap());
+
+        assert!(PackageLock::from_bytes(b"{").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_v3_lockfile_without_root_metadata() {
+        let lock = PackageLock::from_json(V3_LOCKFILE).unwrap();
+        assert_eq!(lock.lockfile_version, 3);
+        assert!(lock.lockfile_version_compat());
+        assert_eq!((lock.name.as_str(), lock.version.as_str()), ("", ""));
+        assert!(lock.dependencies.is_none());
+        assert_eq!(lock.packages.len(), 6);
+
+        let path = "node_modules/@babel/runtime";
+        let runtime = &lock.packages[path];
+        assert_eq!(runtime.get_name(path), "@babel/runtime");
+        assert_eq!(runtime.get_version(), "7.23.2");
+        assert_eq!(lock.packages["node_modules/typescript"].dev, Some(true));
+
+        let newer = V3_LOCKFILE.replace(r#""lockfileVersion": 3"#, r#""lockfileVersion": 4"#);
+        assert!(
+            !PackageLock::from_json(&newer)
+                .unwrap()
+                .lockfile_version_compat()
+        );
     }
 }
@@ -4,13 +4,22 @@
 //! tgz URL, downloading via [`Store`], and creating fuse links via
 //! [`FuseFs`]. All I/O is delegated to those subsystems.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use futures::stream::{self, StreamExt};
+use tracing::{Instrument, field, instrument};
 
+use wasmtimer::std::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::archive;
 use crate::error::OpfsError;
-use crate::package_lock::{LockPackage, PackageLock};
+use crate::install_log::{self, DownloadedPackage, InstallLogEntry};
+use crate::package_lock::{LockPackage, PackageLock, install_name};
 use crate::project::OpfsProject;
+use crate::store::{Store, resolved_marker_path};
 
 /// Types of dependencies that can be omitted during install.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,12 +31,153 @@ pub enum OmitType {
 }
 
 /// Options for [`OpfsProject::install`].
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct InstallOptions {
     /// Maximum concurrent downloads (overrides [`Config::max_concurrent_downloads`])
     pub max_concurrent_downloads: Option<usize>,
     /// Types of dependencies to skip
     pub omit: Vec<OmitType>,
+    /// Install exactly what the lockfile pins without checking peer
+    /// dependencies, like `npm install --legacy-peer-deps`. When `false`,
+    /// peers that cannot be resolved from the lockfile are reported as
+    /// warnings.
+    pub legacy_peer_deps: bool,
+    /// Download attempts per package, including the first (overrides
+    /// [`Config::download_retries`]). `Some(0)` counts as one attempt.
+    pub max_attempts: Option<u32>,
+    /// Called after each package is fetched, from cache or the network
+    pub on_progress: Option<ProgressCallback>,
+    /// Skip packages whose `os`/`cpu` constraints exclude the browser
+    /// (e.g. native `esbuild-linux-x64` binaries). Default: `true`
+    pub skip_platform_mismatch: bool,
+    /// Record failed packages in the [`InstallReport`] and return `Ok`
+    /// instead of failing the install with the first error
+    pub continue_on_error: bool,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent_downloads: None,
+            omit: Vec::new(),
+            legacy_peer_deps: false,
+            max_attempts: None,
+            on_progress: None,
+            skip_platform_mismatch: true,
+            continue_on_error: false,
+        }
+    }
+}
+
+/// `os` value of the install target, as matched against `LockPackage::os`.
+pub const TARGET_OS: &str = "browser";
+/// `cpu` value of the install target, as matched against `LockPackage::cpu`.
+pub const TARGET_CPU: &str = "wasm32";
+
+/// Outcome of an install, per package (`name@version`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallReport {
+    /// Already in the store
+    pub cached: Vec<String>,
+    /// Freshly downloaded
+    pub downloaded: Vec<String>,
+    /// Copied from a local directory (`file:` and workspace dependencies)
+    pub local: Vec<String>,
+    /// Failed to download, verify or link, with the error message
+    pub failed: Vec<(String, String)>,
+    /// Optional packages that failed, with the error message. These never
+    /// fail the install.
+    pub optional_failed: Vec<(String, String)>,
+    /// Required peer dependencies no installed package satisfies, e.g.
+    /// `react-dom: missing peer dependency react@^18`; empty with
+    /// [`InstallOptions::legacy_peer_deps`]
+    pub peer_warnings: Vec<String>,
+}
+
+/// Outcome of checking one installed package (`name@version`); see
+/// [`OpfsProject::verify_installed_packages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallCheck {
+    /// Linked, extracted and, if checked, intact
+    Ok(String),
+    /// No readable `fuse.link` at the install path
+    MissingFuseLink(String),
+    /// The link target is gone from the store
+    MissingStore(String),
+    /// The stored tgz is missing or fails its lockfile hashes
+    IntegrityMismatch(String),
+    /// The store directory lacks its resolved marker
+    ExtractionIncomplete(String),
+}
+
+impl InstallCheck {
+    /// Whether the package is installed correctly.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok(_))
+    }
+}
+
+impl InstallReport {
+    fn sort(&mut self) {
+        self.cached.sort();
+        self.downloaded.sort();
+        self.local.sort();
+        self.failed.sort();
+        self.optional_failed.sort();
+    }
+}
+
+/// Fetch progress of an install, reported through
+/// [`InstallOptions::on_progress`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstallProgress {
+    /// Unique tarballs to fetch
+    pub total_packages: usize,
+    pub downloaded: usize,
+    pub cached: usize,
+    pub failed: usize,
+}
+
+/// Progress callback for [`InstallOptions::on_progress`].
+///
+/// Installs run on the browser's event loop, so the callback is neither
+/// `Send` nor `Sync`; it is invoked from the install future itself.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(InstallProgress)>);
+
+impl ProgressCallback {
+    pub fn new(f: impl Fn(InstallProgress) + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Wrap a JS function, which receives a plain object
+    /// `{ totalPackages, downloaded, cached, failed }`.
+    pub fn from_js(f: js_sys::Function) -> Self {
+        Self::new(move |progress| {
+            let obj = js_sys::Object::new();
+            for (key, value) in [
+                ("totalPackages", progress.total_packages),
+                ("downloaded", progress.downloaded),
+                ("cached", progress.cached),
+                ("failed", progress.failed),
+            ] {
+                let _ = js_sys::Reflect::set(&obj, &key.into(), &(value as f64).into());
+            }
+            if let Err(e) = f.call1(&wasm_bindgen::JsValue::NULL, &obj) {
+                tracing::warn!("progress callback threw: {e:?}");
+            }
+        })
+    }
+
+    fn call(&self, progress: InstallProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback(..)")
+    }
 }
 
 // ── internal grouping ────────────────────────────────────────────────────
@@ -39,51 +189,173 @@ struct PackageGroup {
     integrity: Option<String>,
     shasum: Option<String>,
     target_paths: Vec<String>,
+    /// Every lock entry using this tgz is optional
+    optional: bool,
+}
+
+/// A group whose tgz is on disk, ready to be extracted and linked.
+struct FetchedGroup {
+    name: String,
+    version: String,
+    tgz_url: String,
+    target_paths: Vec<String>,
+    /// `true` when the tgz was downloaded in this session
+    was_fresh: bool,
+    /// Size of the tgz when freshly downloaded, otherwise 0
+    bytes: u64,
+    duration_ms: u64,
+}
+
+/// A local (`file:` or workspace) package, copied into the store instead
+/// of downloaded.
+struct LocalPackage {
+    name: String,
+    version: String,
+    source_dir: PathBuf,
+    target_paths: Vec<String>,
+}
+
+/// Where an installed package came from, for the [`InstallReport`].
+#[derive(Clone, Copy)]
+enum Origin {
+    Cache,
+    Download,
+    Local,
 }
 
 fn should_omit(pkg: &LockPackage, omit: &[OmitType]) -> bool {
     omit.iter().any(|o| match o {
-        OmitType::Dev => pkg.dev == Some(true),
-        OmitType::Optional => pkg.optional == Some(true),
+        OmitType::Dev => pkg.is_dev(),
+        OmitType::Optional => pkg.is_optional(),
     })
 }
 
+/// Whether `pkg`'s `os` and `cpu` constraints allow the given target.
+///
+/// Follows npm semantics: a missing or empty list allows everything,
+/// `!value` entries exclude a platform, and if any plain entries are
+/// present the target must be one of them.
+pub fn platform_matches(pkg: &LockPackage, target_os: &str, target_cpu: &str) -> bool {
+    fn allows(constraint: Option<&serde_json::Value>, target: &str) -> bool {
+        let Some(list) = constraint.and_then(|v| v.as_array()) else {
+            return true;
+        };
+        let entries: Vec<&str> = list.iter().filter_map(|v| v.as_str()).collect();
+        if entries.iter().any(|e| e.strip_prefix('!') == Some(target)) {
+            return false;
+        }
+        let mut allowed = entries.iter().filter(|e| !e.starts_with('!')).peekable();
+        allowed.peek().is_none() || allowed.any(|e| *e == target)
+    }
+
+    allows(pkg.os.as_ref(), target_os) && allows(pkg.cpu.as_ref(), target_cpu)
+}
+
+/// Collect peer dependencies that no installed package satisfies. Peers
+/// marked optional in `peerDependenciesMeta` are not required.
+fn peer_dependency_warnings(lock: &PackageLock, omit: &[OmitType]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (path, pkg) in lock.packages.iter().filter(|(p, _)| !p.is_empty()) {
+        if should_omit(pkg, omit) {
+            continue;
+        }
+        let Some(peers) = &pkg.peer_dependencies else {
+            continue;
+        };
+        for (peer, range) in peers {
+            if !pkg.is_optional_peer(peer) && lock.resolve_package_path(peer, path).is_none() {
+                warnings.push(format!(
+                    "{}: missing peer dependency {peer}@{range}",
+                    pkg.get_name(path)
+                ));
+            }
+        }
+    }
+    warnings.sort();
+    warnings
+}
+
 // ── public entry point ───────────────────────────────────────────────────
 
 /// Install all packages from a lock file.
 ///
 /// Called by [`OpfsProject::install`] — not intended for direct use.
+#[instrument(skip_all, fields(packages = lock.packages.len()))]
 pub(crate) async fn install(
     project: &OpfsProject,
     lock: &PackageLock,
     opts: &InstallOptions,
-) -> Result<(), OpfsError> {
+) -> Result<InstallReport, OpfsError> {
+    let started = Instant::now();
+    let mut log = InstallLogEntry {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        ..Default::default()
+    };
     let omit = &opts.omit;
 
+    let peer_warnings = if opts.legacy_peer_deps {
+        Vec::new()
+    } else {
+        peer_dependency_warnings(lock, omit)
+    };
+    for warning in &peer_warnings {
+        tracing::warn!("{warning}");
+    }
+
     // 1. Group packages by tgz URL (deduplication)
     let mut groups: HashMap<String, PackageGroup> = HashMap::new();
+    // Bundled packages come inside their parent's tarball
+    let mut bundled: Vec<&str> = Vec::new();
+    // Local packages by source directory
+    let mut locals: HashMap<PathBuf, LocalPackage> = HashMap::new();
+    let registry = project.store().registry_config();
+    let cwd = project.cwd();
 
     for (path, pkg) in lock.packages.iter().filter(|(p, _)| !p.is_empty()) {
         if should_omit(pkg, omit) {
             continue;
         }
 
-        // Skip optional packages with platform constraints (binary, won't work in WASM)
-        if pkg.optional == Some(true) && (pkg.os.is_some() || pkg.cpu.is_some()) {
+        if lock.bundling_parent(path).is_some() {
+            bundled.push(path);
+            continue;
+        }
+
+        // Skip native binaries built for other platforms (won't work in WASM)
+        if opts.skip_platform_mismatch && !platform_matches(pkg, TARGET_OS, TARGET_CPU) {
             continue;
         }
 
         let name = pkg.get_name(path).into_owned();
         let version = pkg.get_version().into_owned();
-        let tgz_url = match &pkg.resolved {
-            Some(u) => u.clone(),
+
+        if let Some(local_path) = pkg.local_path() {
+            let source_dir = local_source_dir(&cwd, local_path);
+            locals
+                .entry(source_dir.clone())
+                .or_insert_with(|| LocalPackage {
+                    name,
+                    version,
+                    source_dir,
+                    target_paths: Vec::new(),
+                })
+                .target_paths
+                .push(path.clone());
+            continue;
+        }
+
+        let tgz_url = match registry.tarball_url(&name, &version, pkg.resolved.as_deref()) {
+            Some(u) => u,
             None => {
                 tracing::warn!("{name}@{version}: no resolved URL, skipping");
                 continue;
             }
         };
 
-        groups
+        let group = groups
             .entry(tgz_url.clone())
             .or_insert_with(|| PackageGroup {
                 name,
@@ -92,116 +364,1156 @@ pub(crate) async fn install(
                 integrity: pkg.integrity.clone(),
                 shasum: pkg.shasum.clone(),
                 target_paths: Vec::new(),
-            })
-            .target_paths
-            .push(path.clone());
+                optional: true,
+            });
+        group.target_paths.push(path.clone());
+        group.optional &= pkg.is_optional();
     }
 
+    // Optional packages (e.g. platform-native binaries) may fail without
+    // failing the install.
+    let optional_packages: HashSet<String> = groups
+        .values()
+        .filter(|g| g.optional)
+        .map(|g| format!("{}@{}", g.name, g.version))
+        .collect();
+
     // 2. Fetch all packages (cached or download) with integrity verification
     let store = project.store();
     let fuse = project.fuse_fs();
     let max_concurrent = opts
         .max_concurrent_downloads
-        .unwrap_or(project.config().max_concurrent_downloads);
+        .unwrap_or(project.config().max_concurrent_downloads)
+        .max(1);
+    let retries = opts
+        .max_attempts
+        .unwrap_or(project.config().download_retries)
+        .max(1);
+
+    let mut progress = InstallProgress {
+        total_packages: groups.len(),
+        ..Default::default()
+    };
+
+    // The span records the fetch outcome once every group has settled.
+    let fetch_span = tracing::info_span!(
+        "fetch",
+        total = groups.len(),
+        cached = field::Empty,
+        downloaded = field::Empty,
+        failed = field::Empty,
+    );
 
     // Issue #3: Reuse outer `store` reference — &Store is Copy, no need to
     // re-borrow from project inside each closure.
     let results: Vec<_> = stream::iter(groups.into_values().map(|g| async move {
-        let was_fresh = store
-            .ensure_tgz(
-                &g.name,
-                &g.version,
-                &g.tgz_url,
-                g.integrity.as_deref(),
-                g.shasum.as_deref(),
-            )
-            .await?;
-        Ok::<_, OpfsError>((g.name, g.tgz_url, g.target_paths, was_fresh))
+        let package = format!("{}@{}", g.name, g.version);
+        fetch_group(store, g, retries)
+            .await
+            .map_err(|e| (package, e))
     }))
     .buffer_unordered(max_concurrent)
+    .inspect(|result| {
+        if let Some(on_progress) = &opts.on_progress {
+            match result {
+                Ok(g) if g.was_fresh => progress.downloaded += 1,
+                Ok(_) => progress.cached += 1,
+                Err(_) => progress.failed += 1,
+            }
+            on_progress.call(progress);
+        }
+    })
     .collect()
+    .instrument(fetch_span.clone())
     .await;
 
-    // 3. Create fuse links **concurrently** for all successful fetches, collect errors.
-    //    (Issue #1: previously this was a serial loop — now uses buffer_unordered.)
-    let mut first_error: Option<OpfsError> = None;
+    let downloaded = results
+        .iter()
+        .filter(|r| matches!(r, Ok(g) if g.was_fresh))
+        .count();
+    let cached = results.iter().filter(|r| r.is_ok()).count() - downloaded;
+    let failed = results.len() - downloaded - cached;
+    fetch_span.record("cached", cached);
+    fetch_span.record("downloaded", downloaded);
+    fetch_span.record("failed", failed);
+    fetch_span.in_scope(|| tracing::info!(cached, downloaded, failed, "fetch complete"));
+
+    // 3. Extract and create fuse links **concurrently** for all successful
+    //    fetches, collect errors. Both steps share the download limit:
+    //    thousands of simultaneous OPFS writes thrash the browser.
+    let mut report = InstallReport {
+        peer_warnings,
+        ..Default::default()
+    };
+    let mut failures: Vec<(String, OpfsError)> = Vec::new();
 
     let successful: Vec<_> = results
         .into_iter()
-        .filter_map(|r| match r {
-            Ok(v) => Some(v),
-            Err(e) => {
-                if first_error.is_none() {
-                    first_error = Some(e);
-                }
-                None
+        .filter_map(|r| r.map_err(|failure| failures.push(failure)).ok())
+        .collect();
+
+    for g in &successful {
+        if g.was_fresh {
+            log.total_bytes_downloaded += g.bytes;
+            log.installed.push(DownloadedPackage {
+                name: g.name.clone(),
+                version: g.version.clone(),
+                bytes: g.bytes,
+                duration_ms: g.duration_ms,
+            });
+        } else {
+            log.cached.push(format!("{}@{}", g.name, g.version));
+        }
+    }
+
+    let extract_results: Vec<_> = stream::iter(successful.into_iter().map(|g| {
+        let package = format!("{}@{}", g.name, g.version);
+        async move {
+            let result = extract_group(store, fuse, &g).await;
+            let origin = if g.was_fresh {
+                Origin::Download
+            } else {
+                Origin::Cache
+            };
+            (package, origin, g.target_paths, result)
+        }
+    }))
+    .buffer_unordered(max_concurrent)
+    .collect()
+    .await;
+
+    // Local packages are copied afresh on every install so that edits to
+    // their sources show up.
+    let copy_results: Vec<_> = stream::iter(locals.into_values().map(|local| async move {
+        let out_dir = store.local_package_dir(&local.name, &local.source_dir);
+        let package = format!("{}@{}", local.name, local.version);
+        let result = fuse
+            .copy_package_dir(&local.source_dir, &out_dir)
+            .await
+            .map(|_| out_dir)
+            .map_err(|e| OpfsError::Other(format!("copy local package: {e}")));
+        (package, Origin::Local, local.target_paths, result)
+    }))
+    .buffer_unordered(max_concurrent)
+    .collect()
+    .await;
+
+    let mut extracted = Vec::new();
+    let mut link_jobs = Vec::new();
+    for (package, origin, targets, result) in extract_results.into_iter().chain(copy_results) {
+        match result {
+            Ok(extracted_dir) => {
+                let index = extracted.len();
+                link_jobs.extend(
+                    targets
+                        .into_iter()
+                        .map(|target| (index, extracted_dir.clone(), target)),
+                );
+                extracted.push((package, origin));
             }
-        })
+            Err(e) => failures.push((package, e)),
+        }
+    }
+
+    // Bundled packages link into their parent's extraction directory.
+    // Shorter paths first, so bundles nested in bundles find their parent.
+    bundled.sort_by_key(|path| path.len());
+    let mut link_dirs: HashMap<String, (usize, PathBuf)> = link_jobs
+        .iter()
+        .map(|(index, dir, target)| (target.clone(), (*index, dir.clone())))
         .collect();
+    for path in bundled {
+        let Some(parent) = lock.bundling_parent(path) else {
+            continue;
+        };
+        // The parent failed or was skipped; nothing to link into
+        let Some((index, parent_dir)) = link_dirs.get(parent).cloned() else {
+            continue;
+        };
+        let dir = parent_dir.join("node_modules").join(install_name(path));
+        link_dirs.insert(path.to_string(), (index, dir.clone()));
+        link_jobs.push((index, dir, path.to_string()));
+    }
 
+    // A package is linked at all its paths or none. Its links are written
+    // one at a time so that a package installed at many paths cannot
+    // exceed the limit.
+    let mut package_links = vec![Vec::new(); extracted.len()];
+    for (index, extracted_dir, target) in link_jobs {
+        package_links[index].push((extracted_dir, PathBuf::from(target)));
+    }
+    let mut link_errors: HashMap<usize, OpfsError> = HashMap::new();
     let link_results: Vec<_> = stream::iter(
-        successful
+        package_links
             .into_iter()
-            .map(|(name, url, targets, was_fresh)| {
-                let tgz_path = store.tgz_path(&name, &url);
-                async move {
-                    // If the tgz was re-downloaded (e.g. cached copy failed
-                    // integrity), delete the stale sentinel so that
-                    // extract_tgz_to_dir is forced to re-extract.
-                    if was_fresh {
-                        let sentinel = std::path::PathBuf::from(format!(
-                            "{}._resolved",
-                            tgz_path.with_extension("").display()
-                        ));
-                        let _ = tokio_fs_ext::remove_file(&sentinel).await;
-                    }
-                    link_and_warm_cache(fuse, &tgz_path, &targets).await
-                }
-            }),
+            .enumerate()
+            .map(|(index, links)| async move { (index, link_and_warm_cache(fuse, &links).await) }),
     )
     .buffer_unordered(max_concurrent)
     .collect()
     .await;
-
-    for result in link_results {
+    for (index, result) in link_results {
         if let Err(e) = result {
-            if first_error.is_none() {
-                first_error = Some(e);
+            link_errors.insert(index, e);
+        }
+    }
+
+    for (index, (package, origin)) in extracted.into_iter().enumerate() {
+        match (link_errors.remove(&index), origin) {
+            (Some(e), _) => failures.push((package, e)),
+            (None, Origin::Cache) => report.cached.push(package),
+            (None, Origin::Download) => report.downloaded.push(package),
+            (None, Origin::Local) => report.local.push(package),
+        }
+    }
+
+    let mut first_error: Option<OpfsError> = None;
+    for (package, e) in failures {
+        if optional_packages.contains(&package) {
+            tracing::warn!("skipping optional package {package}: {e}");
+            report.optional_failed.push((package, e.to_string()));
+            continue;
+        }
+        log.errors.push(e.to_string());
+        report.failed.push((package, e.to_string()));
+        first_error.get_or_insert(e);
+    }
+    report.sort();
+
+    // 4. Record telemetry — a failure here must not fail the install.
+    log.duration_ms = started.elapsed().as_millis() as u64;
+    let log_path = project.cwd().join(install_log::INSTALL_LOG_FILE);
+    if let Err(e) = install_log::append(&log_path, &log).await {
+        tracing::warn!("failed to write install log {}: {e}", log_path.display());
+    }
+
+    match first_error {
+        Some(e) if !opts.continue_on_error => Err(e),
+        _ => Ok(report),
+    }
+}
+
+/// Check every package of `lock` at its install path: linked, present in
+/// the store and completely extracted, and with `verify_integrity` also
+/// that its stored tgz still matches the lockfile hashes. Packages `opts`
+/// skips on install are skipped here too.
+///
+/// Called by [`OpfsProject::verify_installed_packages`].
+pub(crate) async fn verify_installed(
+    project: &OpfsProject,
+    lock: &PackageLock,
+    verify_integrity: bool,
+    opts: &InstallOptions,
+) -> Vec<InstallCheck> {
+    let store = project.store();
+    let registry = store.registry_config();
+    let max_concurrent = opts
+        .max_concurrent_downloads
+        .unwrap_or(project.config().max_concurrent_downloads)
+        .max(1);
+    let checks = lock
+        .packages
+        .iter()
+        .filter(|(path, _)| !path.is_empty())
+        .filter(|(_, pkg)| !should_omit(pkg, &opts.omit))
+        .filter(|(_, pkg)| {
+            !opts.skip_platform_mismatch || platform_matches(pkg, TARGET_OS, TARGET_CPU)
+        })
+        .filter_map(|(path, pkg)| {
+            let name = pkg.get_name(path);
+            let bundled = lock.bundling_parent(path).is_some();
+            // Packages without a tarball are never installed
+            let tgz_url = if pkg.local_path().is_some() || bundled {
+                None
+            } else {
+                Some(registry.tarball_url(&name, &pkg.get_version(), pkg.resolved.as_deref())?)
+            };
+            let package = format!("{name}@{}", pkg.get_version());
+            Some(async move {
+                let Ok(Some(target_dir)) = project.get_fuse_link_target(path).await else {
+                    return (!pkg.is_optional()).then_some(InstallCheck::MissingFuseLink(package));
+                };
+                if !tokio_fs_ext::metadata(&target_dir)
+                    .await
+                    .is_ok_and(|m| m.is_dir())
+                {
+                    return Some(InstallCheck::MissingStore(package));
+                }
+                // Bundled packages live inside their parent's extraction
+                if bundled {
+                    return Some(InstallCheck::Ok(package));
+                }
+                if tokio_fs_ext::metadata(resolved_marker_path(&target_dir))
+                    .await
+                    .is_err()
+                {
+                    return Some(InstallCheck::ExtractionIncomplete(package));
+                }
+                if let (true, Some(tgz_url)) = (verify_integrity, tgz_url) {
+                    let intact = tokio_fs_ext::read(store.tgz_path(&name, &tgz_url))
+                        .await
+                        .is_ok_and(|bytes| {
+                            !archive::verify_integrity(
+                                &bytes,
+                                pkg.integrity.as_deref(),
+                                pkg.shasum.as_deref(),
+                            )
+                            .is_failed()
+                        });
+                    if !intact {
+                        return Some(InstallCheck::IntegrityMismatch(package));
+                    }
+                }
+                Some(InstallCheck::Ok(package))
+            })
+        });
+
+    stream::iter(checks)
+        .buffered(max_concurrent)
+        .filter_map(|check| async move { check })
+        .collect()
+        .await
+}
+
+/// Install one package at `install_path` without a lockfile.
+///
+/// Called by [`OpfsProject::install_package`], which validates the path.
+#[instrument(skip_all, fields(package = %name, version = %version, path = %install_path))]
+pub(crate) async fn install_package(
+    project: &OpfsProject,
+    name: &str,
+    version: &str,
+    tgz_url: &str,
+    integrity: Option<&str>,
+    shasum: Option<&str>,
+    install_path: &str,
+) -> Result<(), OpfsError> {
+    let group = PackageGroup {
+        name: name.to_string(),
+        version: version.to_string(),
+        tgz_url: tgz_url.to_string(),
+        integrity: integrity.map(str::to_string),
+        shasum: shasum.map(str::to_string),
+        target_paths: vec![install_path.to_string()],
+        optional: false,
+    };
+    let fetched = fetch_group(project.store(), group, project.config().download_retries).await?;
+    let extracted_dir = extract_group(project.store(), project.fuse_fs(), &fetched).await?;
+    link_and_warm_cache(
+        project.fuse_fs(),
+        &[(extracted_dir, PathBuf::from(install_path))],
+    )
+    .await
+}
+
+/// Write a shim to `<node_modules>/.bin/<name>` for every executable in
+/// `lock`, next to the package that provides it. OPFS has no symlinks, so
+/// each shim is a small script that `require`s the real file.
+///
+/// Called by [`OpfsProject::install_bin_links`]. Returns the number of
+/// shims written.
+pub(crate) async fn install_bin_links(
+    project: &OpfsProject,
+    lock: &PackageLock,
+) -> Result<usize, OpfsError> {
+    let mut written = 0;
+    for (path, pkg) in lock.packages.iter().filter(|(p, _)| !p.is_empty()) {
+        let Some(index) = path.rfind("node_modules/") else {
+            continue;
+        };
+        let (node_modules, package_dir) = path.split_at(index + "node_modules/".len());
+        let bin_dir = PathBuf::from(node_modules).join(".bin");
+        for (name, bin_path) in pkg.get_bin_entries(&pkg.get_name(path)) {
+            if bin_path.split('/').any(|c| c == "..") {
+                tracing::warn!("{path}: skipping bin {name} outside the package");
+                continue;
             }
+            let shim = format!("#!/usr/bin/env node\nrequire(\"../{package_dir}/{bin_path}\");\n");
+            project.create_dir_all(&bin_dir).await?;
+            project.write(bin_dir.join(&name), shim).await?;
+            written += 1;
         }
     }
+    Ok(written)
+}
 
-    if let Some(e) = first_error {
-        return Err(e);
+/// Ensure one group's tgz is in the store.
+async fn fetch_group(
+    store: &Store,
+    g: PackageGroup,
+    retries: u32,
+) -> Result<FetchedGroup, OpfsError> {
+    let fetch_started = Instant::now();
+    let was_fresh = store
+        .ensure_tgz_with_retries(
+            &g.name,
+            &g.version,
+            &g.tgz_url,
+            g.integrity.as_deref(),
+            g.shasum.as_deref(),
+            retries,
+        )
+        .await?;
+    let bytes = if was_fresh {
+        tokio_fs_ext::metadata(store.tgz_path(&g.name, &g.tgz_url))
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    Ok(FetchedGroup {
+        name: g.name,
+        version: g.version,
+        tgz_url: g.tgz_url,
+        target_paths: g.target_paths,
+        was_fresh,
+        bytes,
+        duration_ms: fetch_started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Absolute directory of a local package whose lockfile path (relative to
+/// the lockfile in `cwd`) is `local_path`.
+pub(crate) fn local_source_dir(cwd: &std::path::Path, local_path: &str) -> PathBuf {
+    normalize_path(&cwd.join(local_path))
+}
+
+/// Resolve `.` and `..` components of an absolute path lexically.
+fn normalize_path(path: &std::path::Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
     }
+    normalized
+}
 
-    Ok(())
+/// Extract a fetched group's tgz, returning the extraction directory.
+async fn extract_group(
+    store: &Store,
+    fuse: &crate::fuse_fs::FuseFs,
+    g: &FetchedGroup,
+) -> Result<PathBuf, OpfsError> {
+    let tgz_path = store.tgz_path(&g.name, &g.tgz_url);
+    // If the tgz was re-downloaded (e.g. cached copy failed integrity),
+    // delete the stale sentinel so that extract_tgz_to_dir is forced to
+    // re-extract.
+    if g.was_fresh {
+        let _ = tokio_fs_ext::remove_file(resolved_marker_path(&tgz_path.with_extension(""))).await;
+    }
+    fuse.extract_tgz_to_dir(&tgz_path)
+        .await
+        .map_err(|e| OpfsError::Other(format!("extract tgz: {e}")))
 }
 
-/// Create fuse links and warm the cache for a set of target paths.
+/// Create the fuse link at each `(extracted_dir, target)` of one package,
+/// all or nothing (see [`FuseFs::create_fuse_links`](crate::fuse_fs::FuseFs::create_fuse_links)),
+/// and warm the cache for them.
 async fn link_and_warm_cache(
     fuse: &crate::fuse_fs::FuseFs,
-    tgz_path: &std::path::Path,
-    targets: &[String],
+    links: &[(PathBuf, PathBuf)],
 ) -> std::result::Result<(), OpfsError> {
-    let extracted_dir = fuse
-        .extract_tgz_to_dir(tgz_path)
+    let pairs: Vec<_> = links
+        .iter()
+        .map(|(extracted_dir, dst)| (extracted_dir.as_path(), dst.as_path()))
+        .collect();
+    fuse.create_fuse_links(&pairs, 1)
         .await
-        .map_err(|e| OpfsError::Other(format!("extract tgz: {e}")))?;
+        .map_err(|e| OpfsError::Other(format!("fuse link: {e}")))?;
+    for (extracted_dir, dst) in links {
+        fuse.warm_link_cache(dst, extracted_dir);
+    }
+    Ok(())
+}
 
-    // Create all fuse links concurrently within this group.
-    futures::future::try_join_all(targets.iter().map(|target| {
-        let extracted_dir = &extracted_dir;
-        async move {
-            let dst = std::path::PathBuf::from(target);
-            fuse.create_fuse_link(extracted_dir, &dst)
+#[cfg(test)]
+mod tests {
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn package(os: Option<serde_json::Value>, cpu: Option<serde_json::Value>) -> LockPackage {
+        LockPackage {
+            os,
+            cpu,
+            ..Default::default()
+        }
+    }
+
+    fn tgz_url(name: &str) -> String {
+        format!("https://registry.npmjs.org/{name}/-/{name}-1.0.0.tgz")
+    }
+
+    /// Project under `base` whose store already holds `name@1.0.0` for
+    /// each of `names`, so installs never hit the network.
+    async fn project_with_store(base: &std::path::Path, names: &[&str]) -> OpfsProject {
+        use crate::archive::{PackFile, gzip};
+        use crate::config::Config;
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+        let project = OpfsProject::new(Config {
+            store_root: base.join("stores"),
+            ..Default::default()
+        });
+        project.set_cwd(base);
+
+        for name in names {
+            let tgz = gzip(&[PackFile::new(
+                "package/package.json",
+                format!(r#"{{"name":"{name}"}}"#).into_bytes(),
+            )])
+            .unwrap();
+            let tgz_path = project.store().tgz_path(name, &tgz_url(name));
+            tokio_fs_ext::create_dir_all(tgz_path.parent().unwrap())
                 .await
-                .map_err(|e| OpfsError::Other(format!("fuse link for {target}: {e}")))?;
-            fuse.warm_link_cache(&dst, extracted_dir);
-            Ok::<_, OpfsError>(())
+                .unwrap();
+            tokio_fs_ext::write(&tgz_path, tgz).await.unwrap();
         }
-    }))
-    .await?;
+        project
+    }
 
-    Ok(())
+    /// Seed a cached tgz for `name` that fails to extract, so the package
+    /// fails without touching the network.
+    async fn seed_corrupt_tgz(project: &OpfsProject, name: &str) {
+        let tgz_path = project.store().tgz_path(name, &tgz_url(name));
+        tokio_fs_ext::create_dir_all(tgz_path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio_fs_ext::write(&tgz_path, b"not a tarball")
+            .await
+            .unwrap();
+    }
+
+    /// Lock with the given `(path, package)` entries.
+    fn lock_with(packages: Vec<(&str, LockPackage)>) -> PackageLock {
+        let mut lock =
+            PackageLock::from_json(r#"{"lockfileVersion": 3, "packages": {"": {}}}"#).unwrap();
+        for (path, package) in packages {
+            lock.packages.insert(path.to_string(), package);
+        }
+        lock
+    }
+
+    fn registry_package(name: &str) -> LockPackage {
+        LockPackage {
+            name: Some(name.to_string()),
+            version: Some("1.0.0".to_string()),
+            resolved: Some(tgz_url(name)),
+            ..Default::default()
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_links_every_target_with_concurrency_one() {
+        let base = std::path::Path::new("/test_install_bounded_links");
+        let project = project_with_store(base, &["a", "b", "c"]).await;
+        let lock = lock_with(vec![
+            ("node_modules/a", registry_package("a")),
+            ("node_modules/b", registry_package("b")),
+            ("node_modules/b/node_modules/a", registry_package("a")),
+            ("node_modules/c", registry_package("c")),
+        ]);
+
+        let opts = InstallOptions {
+            max_concurrent_downloads: Some(1),
+            ..Default::default()
+        };
+        let report = project.install(&lock, &opts).await.unwrap();
+        assert_eq!(report.cached, ["a@1.0.0", "b@1.0.0", "c@1.0.0"]);
+        assert!(report.failed.is_empty());
+        for path in lock.packages.keys().filter(|p| !p.is_empty()) {
+            assert!(
+                tokio_fs_ext::metadata(base.join(path).join("fuse.link"))
+                    .await
+                    .is_ok(),
+                "missing fuse link for {path}"
+            );
+        }
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_continues_past_failed_optional_package() {
+        let base = std::path::Path::new("/test_install_optional_failure");
+        let project = project_with_store(base, &["a"]).await;
+        seed_corrupt_tgz(&project, "opt").await;
+        let mut lock = lock_with(vec![
+            ("node_modules/a", registry_package("a")),
+            (
+                "node_modules/opt",
+                LockPackage {
+                    optional: Some(true),
+                    ..registry_package("opt")
+                },
+            ),
+        ]);
+        let opts = InstallOptions::default();
+
+        let report = project.install(&lock, &opts).await.unwrap();
+        assert_eq!(report.cached, ["a@1.0.0"]);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.optional_failed.len(), 1);
+        assert_eq!(report.optional_failed[0].0, "opt@1.0.0");
+
+        // The same failure aborts the install for a required package
+        lock.packages.get_mut("node_modules/opt").unwrap().optional = None;
+        assert!(project.install(&lock, &opts).await.is_err());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_continue_on_error() {
+        let base = std::path::Path::new("/test_install_continue_on_error");
+        let project = project_with_store(base, &["a", "c"]).await;
+        seed_corrupt_tgz(&project, "broken").await;
+        let lock = lock_with(vec![
+            ("node_modules/a", registry_package("a")),
+            ("node_modules/broken", registry_package("broken")),
+            ("node_modules/c", registry_package("c")),
+        ]);
+        let opts = InstallOptions {
+            continue_on_error: true,
+            ..Default::default()
+        };
+
+        let report = project.install(&lock, &opts).await.unwrap();
+        assert_eq!(report.cached, ["a@1.0.0", "c@1.0.0"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "broken@1.0.0");
+        assert!(report.optional_failed.is_empty());
+
+        // Everything else is installed
+        for name in ["a", "c"] {
+            assert_eq!(
+                project
+                    .read_to_string(format!("node_modules/{name}/package.json"))
+                    .await
+                    .unwrap(),
+                format!(r#"{{"name":"{name}"}}"#)
+            );
+        }
+        assert!(!project.exists("node_modules/broken").await.unwrap());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_links_bundled_dependencies_from_parent() {
+        use crate::archive::{PackFile, gzip};
+
+        let base = std::path::Path::new("/test_install_bundled");
+        let project = project_with_store(base, &["parent"]).await;
+        let tgz = gzip(&[
+            PackFile::new("package/package.json", br#"{"name":"parent"}"#.to_vec()),
+            PackFile::new(
+                "package/node_modules/child/package.json",
+                br#"{"name":"child"}"#.to_vec(),
+            ),
+            PackFile::new(
+                "package/node_modules/child/index.js",
+                b"module.exports = 'bundled'".to_vec(),
+            ),
+        ])
+        .unwrap();
+        let tgz_path = project.store().tgz_path("parent", &tgz_url("parent"));
+        tokio_fs_ext::write(&tgz_path, tgz).await.unwrap();
+
+        let lock = lock_with(vec![
+            (
+                "node_modules/parent",
+                LockPackage {
+                    bundled_dependencies: Some(crate::package_lock::BundledDependencies::List(
+                        vec!["child".to_string()],
+                    )),
+                    ..registry_package("parent")
+                },
+            ),
+            (
+                "node_modules/parent/node_modules/child",
+                LockPackage {
+                    // Never copied: it comes inside the parent's tarball
+                    resolved: Some("file:missing-child".to_string()),
+                    ..registry_package("child")
+                },
+            ),
+        ]);
+
+        let report = project
+            .install(&lock, &InstallOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(report.cached, ["parent@1.0.0"]);
+        assert_eq!(
+            project
+                .read_to_string("node_modules/parent/node_modules/child/index.js")
+                .await
+                .unwrap(),
+            "module.exports = 'bundled'"
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_git_dependency_survives_prune() {
+        use crate::archive::{PackFile, gzip};
+
+        let base = std::path::Path::new("/test_install_git_dep");
+        let project = project_with_store(base, &[]).await;
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        let git = format!("git+https://github.com/org/dep.git#{sha}");
+        let tgz_url = project
+            .registry_config()
+            .tarball_url("dep", "1.0.0", Some(&git))
+            .unwrap();
+        assert_eq!(
+            tgz_url,
+            format!("https://github.com/org/dep/archive/{sha}.tar.gz")
+        );
+
+        // Host archives use `<repo>-<sha>/` as their root directory
+        let tgz = gzip(&[PackFile::new(
+            format!("dep-{sha}/package.json"),
+            br#"{"name":"dep"}"#.to_vec(),
+        )])
+        .unwrap();
+        let tgz_path = project.store().tgz_path("dep", &tgz_url);
+        tokio_fs_ext::create_dir_all(tgz_path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio_fs_ext::write(&tgz_path, tgz).await.unwrap();
+
+        let lock = lock_with(vec![(
+            "node_modules/dep",
+            LockPackage {
+                resolved: Some(git),
+                ..registry_package("dep")
+            },
+        )]);
+        let report = project.install(&lock, &Default::default()).await.unwrap();
+        assert_eq!(report.cached, ["dep@1.0.0"]);
+        let extract_dir = tgz_path.with_extension("");
+        assert!(extract_dir.ends_with(format!("{sha}.tar")));
+        assert!(
+            tokio_fs_ext::metadata(resolved_marker_path(&extract_dir))
+                .await
+                .is_ok()
+        );
+
+        let pruned = project
+            .prune_store(&lock, crate::store::PruneOptions::default())
+            .await
+            .unwrap();
+        assert!(pruned.removed_packages.is_empty());
+        assert!(tokio_fs_ext::metadata(&tgz_path).await.is_ok());
+        assert_eq!(
+            project
+                .read_to_string("node_modules/dep/package.json")
+                .await
+                .unwrap(),
+            r#"{"name":"dep"}"#
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_alias() {
+        let base = std::path::Path::new("/test_install_alias");
+        let project = project_with_store(base, &["preact"]).await;
+        // "react": "npm:preact@1.0.0"
+        let lock = lock_with(vec![("node_modules/react", registry_package("preact"))]);
+
+        let report = project.install(&lock, &Default::default()).await.unwrap();
+        assert_eq!(report.cached, ["preact@1.0.0"]);
+        assert_eq!(
+            project
+                .read_to_string("node_modules/react/package.json")
+                .await
+                .unwrap(),
+            r#"{"name":"preact"}"#
+        );
+        assert!(!project.exists("node_modules/preact").await.unwrap());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_package() {
+        let base = std::path::Path::new("/test_install_single");
+        let project = project_with_store(base, &["a"]).await;
+
+        for bad_path in ["src/a", "node_modules/", "node_modules/../a"] {
+            assert!(
+                project
+                    .install_package("a", "1.0.0", &tgz_url("a"), None, None, bad_path)
+                    .await
+                    .is_err(),
+                "{bad_path}"
+            );
+        }
+
+        project
+            .install_package("a", "1.0.0", &tgz_url("a"), None, None, "node_modules/a")
+            .await
+            .unwrap();
+        assert_eq!(
+            project
+                .read_to_string("node_modules/a/package.json")
+                .await
+                .unwrap(),
+            r#"{"name":"a"}"#
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_bin_links() {
+        let base = std::path::Path::new("/test_install_bin_links");
+        let project = project_with_store(base, &[]).await;
+        let lock = lock_with(vec![
+            (
+                "node_modules/@scope/tool",
+                LockPackage {
+                    bin: Some(serde_json::json!("./cli.js")),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/a/node_modules/b",
+                LockPackage {
+                    bin: Some(serde_json::json!({ "b-cli": "bin/b.js" })),
+                    ..Default::default()
+                },
+            ),
+            ("node_modules/plain", LockPackage::default()),
+        ]);
+
+        assert_eq!(project.install_bin_links(&lock).await.unwrap(), 2);
+        assert_eq!(
+            project
+                .read_to_string("node_modules/.bin/tool")
+                .await
+                .unwrap(),
+            "#!/usr/bin/env node\nrequire(\"../@scope/tool/cli.js\");\n"
+        );
+        assert_eq!(
+            project
+                .read_to_string("node_modules/a/node_modules/.bin/b-cli")
+                .await
+                .unwrap(),
+            "#!/usr/bin/env node\nrequire(\"../b/bin/b.js\");\n"
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_from_file() {
+        let base = std::path::Path::new("/test_install_from_file");
+        let project = project_with_store(base, &["a"]).await;
+        let lock = serde_json::json!({
+            "lockfileVersion": 3,
+            "packages": {
+                "": {},
+                "node_modules/a": {
+                    "version": "1.0.0",
+                    "resolved": tgz_url("a"),
+                },
+            },
+        });
+        project.create_dir_all("locks").await.unwrap();
+        project
+            .write("locks/app-lock.json", lock.to_string())
+            .await
+            .unwrap();
+
+        let report = project
+            .install_from_file("locks/app-lock.json", &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(report.cached, ["a@1.0.0"]);
+        assert!(project.exists("node_modules/a/package.json").await.unwrap());
+
+        assert!(
+            project
+                .install_from_file("locks/missing.json", &Default::default())
+                .await
+                .is_err()
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_verify_installed_packages() {
+        let base = std::path::Path::new("/test_verify_installed");
+        let project = project_with_store(base, &["a", "b"]).await;
+        let mut lock = lock_with(vec![
+            ("node_modules/a", registry_package("a")),
+            ("node_modules/b", registry_package("b")),
+        ]);
+        let opts = InstallOptions::default();
+        project.install(&lock, &opts).await.unwrap();
+
+        let check = |mut checks: Vec<InstallCheck>| {
+            checks.sort_by_key(|c| format!("{c:?}"));
+            checks
+        };
+        assert_eq!(
+            check(project.verify_installed_packages(&lock, true, &opts).await),
+            [
+                InstallCheck::Ok("a@1.0.0".to_string()),
+                InstallCheck::Ok("b@1.0.0".to_string()),
+            ]
+        );
+
+        // Not installed, interrupted extraction and corrupt tgz
+        lock.packages
+            .insert("node_modules/c".to_string(), registry_package("c"));
+        let b_dir = project
+            .store()
+            .tgz_path("b", &tgz_url("b"))
+            .with_extension("");
+        tokio_fs_ext::remove_file(resolved_marker_path(&b_dir))
+            .await
+            .unwrap();
+        lock.packages.get_mut("node_modules/a").unwrap().integrity =
+            Some("sha512-AAAA".to_string());
+
+        assert_eq!(
+            check(project.verify_installed_packages(&lock, true, &opts).await),
+            [
+                InstallCheck::ExtractionIncomplete("b@1.0.0".to_string()),
+                InstallCheck::IntegrityMismatch("a@1.0.0".to_string()),
+                InstallCheck::MissingFuseLink("c@1.0.0".to_string()),
+            ]
+        );
+        assert!(
+            project
+                .verify_installed_packages(&lock, false, &opts)
+                .await
+                .contains(&InstallCheck::Ok("a@1.0.0".to_string()))
+        );
+
+        // Packages the install skipped are not reported missing
+        lock.packages.get_mut("node_modules/c").unwrap().dev = Some(true);
+        lock.packages.insert(
+            "node_modules/native".to_string(),
+            LockPackage {
+                os: Some(serde_json::json!(["linux"])),
+                ..registry_package("native")
+            },
+        );
+        let dev_omitted = InstallOptions {
+            omit: vec![OmitType::Dev],
+            ..Default::default()
+        };
+        let checks = project
+            .verify_installed_packages(&lock, false, &dev_omitted)
+            .await;
+        assert!(
+            !checks
+                .iter()
+                .any(|c| matches!(c, InstallCheck::MissingFuseLink(_)))
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_copies_local_packages() {
+        let base = std::path::Path::new("/test_install_local");
+        let project = project_with_store(base, &[]).await;
+        for (path, content) in [
+            ("packages/app-utils/package.json", r#"{"name":"app-utils"}"#),
+            ("packages/app-utils/lib/index.js", "export default 1"),
+            ("packages/app-utils/node_modules/dep/index.js", "ignored"),
+            ("vendor/legacy/index.js", "module.exports = 2"),
+            ("vendor/legacy-next/index.js", "module.exports = 3"),
+        ] {
+            let path = base.join(path);
+            tokio_fs_ext::create_dir_all(path.parent().unwrap())
+                .await
+                .unwrap();
+            tokio_fs_ext::write(&path, content).await.unwrap();
+        }
+
+        let lock = lock_with(vec![
+            // npm workspaces: a link to the workspace directory
+            (
+                "node_modules/app-utils",
+                LockPackage {
+                    resolved: Some("packages/app-utils".to_string()),
+                    link: Some(true),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/legacy",
+                LockPackage {
+                    version: Some("2.0.0".to_string()),
+                    resolved: Some("file:vendor/legacy".to_string()),
+                    ..Default::default()
+                },
+            ),
+            // Same name from another source: copied separately
+            (
+                "node_modules/legacy-next",
+                LockPackage {
+                    name: Some("legacy".to_string()),
+                    version: Some("3.0.0".to_string()),
+                    resolved: Some("file:vendor/legacy-next".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let report = project
+            .install(&lock, &InstallOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            report.local,
+            ["app-utils@unknown", "legacy@2.0.0", "legacy@3.0.0"]
+        );
+        assert!(report.downloaded.is_empty() && report.failed.is_empty());
+        assert_eq!(
+            project
+                .read_to_string("node_modules/app-utils/lib/index.js")
+                .await
+                .unwrap(),
+            "export default 1"
+        );
+        assert_eq!(
+            project
+                .read_to_string("node_modules/legacy/index.js")
+                .await
+                .unwrap(),
+            "module.exports = 2"
+        );
+        assert_eq!(
+            project
+                .read_to_string("node_modules/legacy-next/index.js")
+                .await
+                .unwrap(),
+            "module.exports = 3"
+        );
+        let copy = project
+            .store()
+            .local_package_dir("app-utils", &base.join("packages/app-utils"));
+        assert!(
+            tokio_fs_ext::metadata(copy.join("node_modules"))
+                .await
+                .is_err()
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_install_reports_peer_warnings() {
+        let base = std::path::Path::new("/test_install_peer_warnings");
+        let project = project_with_store(base, &["a"]).await;
+        let lock = lock_with(vec![(
+            "node_modules/a",
+            LockPackage {
+                peer_dependencies: Some(HashMap::from([("b".to_string(), "^2".to_string())])),
+                ..registry_package("a")
+            },
+        )]);
+
+        let report = project.install(&lock, &Default::default()).await.unwrap();
+        assert_eq!(report.peer_warnings, ["a: missing peer dependency b@^2"]);
+        let legacy = InstallOptions {
+            legacy_peer_deps: true,
+            ..Default::default()
+        };
+        let report = project.install(&lock, &legacy).await.unwrap();
+        assert!(report.peer_warnings.is_empty());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    fn test_peer_dependency_warnings() {
+        let lock = PackageLock::from_json(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {},
+                    "node_modules/react-dom": {
+                        "version": "18.0.0",
+                        "peerDependencies": { "react": "^18.0.0", "scheduler": "*" }
+                    },
+                    "node_modules/debug": {
+                        "version": "4.3.4",
+                        "peerDependencies": { "supports-color": "*" },
+                        "peerDependenciesMeta": { "supports-color": { "optional": true } }
+                    },
+                    "node_modules/scheduler": { "version": "0.23.0" },
+                    "node_modules/tool": {
+                        "version": "1.0.0",
+                        "dev": true,
+                        "peerDependencies": { "typescript": "*" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            peer_dependency_warnings(&lock, &[OmitType::Dev]),
+            ["react-dom: missing peer dependency react@^18.0.0"]
+        );
+        assert_eq!(peer_dependency_warnings(&lock, &[]).len(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_platform_matches() {
+        use serde_json::json;
+
+        assert!(platform_matches(&package(None, None), "browser", "wasm32"));
+        assert!(platform_matches(
+            &package(Some(json!([])), None),
+            "browser",
+            "wasm32"
+        ));
+        assert!(!platform_matches(
+            &package(Some(json!(["linux"])), Some(json!(["x64"]))),
+            "browser",
+            "wasm32"
+        ));
+        assert!(!platform_matches(
+            &package(None, Some(json!(["arm64", "x64"]))),
+            "browser",
+            "wasm32"
+        ));
+        assert!(platform_matches(
+            &package(Some(json!(["!win32"])), None),
+            "browser",
+            "wasm32"
+        ));
+        assert!(!platform_matches(
+            &package(Some(json!(["!browser"])), None),
+            "browser",
+            "wasm32"
+        ));
+        assert!(platform_matches(
+            &package(Some(json!(["linux", "browser"])), None),
+            "browser",
+            "wasm32"
+        ));
+    }
 }
@@ -0,0 +1,389 @@
+//! `pnpm-lock.yaml` (v6 and v9) parsing and conversion to [`PackageLock`].
+//!
+//! pnpm records a package graph rather than a `node_modules` tree, so
+//! [`PnpmLock::to_package_lock`] lays the graph out npm-style: direct
+//! dependencies at the top level, transitive dependencies hoisted to the
+//! top level when free and nested under their dependent otherwise.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::package_lock::{LockPackage, PackageLock, ResolvedPackage, RootDependency};
+use crate::registry::{DEFAULT_REGISTRY, registry_tarball_url};
+
+/// A dependency entry of an importer (`specifier` + `version` since v6).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum PnpmDependency {
+    Detailed {
+        specifier: Option<String>,
+        version: String,
+    },
+    Version(String),
+}
+
+impl PnpmDependency {
+    /// The resolved version reference (may carry a peer suffix or alias).
+    pub fn version(&self) -> &str {
+        match self {
+            Self::Detailed { version, .. } | Self::Version(version) => version,
+        }
+    }
+}
+
+/// Direct dependencies of a project (`importers.<path>` in v9, the
+/// document root for single-project v6 lockfiles).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PnpmImporter {
+    #[serde(default)]
+    pub dependencies: HashMap<String, PnpmDependency>,
+    #[serde(default, rename = "devDependencies")]
+    pub dev_dependencies: HashMap<String, PnpmDependency>,
+    #[serde(default, rename = "optionalDependencies")]
+    pub optional_dependencies: HashMap<String, PnpmDependency>,
+}
+
+/// `resolution` of a package.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PnpmResolution {
+    pub integrity: Option<String>,
+    /// Present for packages not served from the default registry
+    pub tarball: Option<String>,
+}
+
+/// An entry of the `packages` section.
+///
+/// In v9 dependency edges live in `snapshots`; in v6 they are here.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PnpmPackage {
+    #[serde(default)]
+    pub resolution: PnpmResolution,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    #[serde(default, rename = "optionalDependencies")]
+    pub optional_dependencies: HashMap<String, String>,
+    pub dev: Option<bool>,
+    pub optional: Option<bool>,
+    pub os: Option<Vec<String>>,
+    pub cpu: Option<Vec<String>>,
+}
+
+/// An entry of the v9 `snapshots` section.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PnpmSnapshot {
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    #[serde(default, rename = "optionalDependencies")]
+    pub optional_dependencies: HashMap<String, String>,
+    pub optional: Option<bool>,
+}
+
+/// Represents a complete `pnpm-lock.yaml` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PnpmLock {
+    #[serde(rename = "lockfileVersion", deserialize_with = "de_lockfile_version")]
+    pub lockfile_version: String,
+    #[serde(default)]
+    pub importers: HashMap<String, PnpmImporter>,
+    /// Root-level dependencies of single-project v6 lockfiles
+    #[serde(flatten)]
+    pub root: PnpmImporter,
+    #[serde(default)]
+    pub packages: HashMap<String, PnpmPackage>,
+    #[serde(default)]
+    pub snapshots: HashMap<String, PnpmSnapshot>,
+}
+
+/// `lockfileVersion` is a string (`'9.0'`) in recent lockfiles and a
+/// number (`5.4`) in older ones.
+fn de_lockfile_version<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Version {
+        Str(String),
+        Num(f64),
+    }
+    Ok(match Version::deserialize(d)? {
+        Version::Str(s) => s,
+        Version::Num(n) => n.to_string(),
+    })
+}
+
+impl PnpmLock {
+    /// Parse from a YAML string.
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_norway::Error> {
+        serde_norway::from_str(yaml)
+    }
+
+    /// Convert to an npm-style [`PackageLock`], reconstructing tarball URLs
+    /// against [`DEFAULT_REGISTRY`].
+    pub fn to_package_lock(&self) -> PackageLock {
+        self.to_package_lock_with_registry(DEFAULT_REGISTRY)
+    }
+
+    /// Convert to an npm-style [`PackageLock`], reconstructing tarball URLs
+    /// against `registry`.
+    pub fn to_package_lock_with_registry(&self, registry: &str) -> PackageLock {
+        let registry = registry.trim_end_matches('/');
+        let root = self.importers.get(".").unwrap_or(&self.root);
+
+        let sections = [
+            (&root.dependencies, false, false),
+            (&root.optional_dependencies, false, true),
+            (&root.dev_dependencies, true, false),
+        ];
+        let mut roots = Vec::new();
+        for (deps, dev, optional) in sections {
+            let mut sorted: Vec<_> = deps.iter().collect();
+            sorted.sort_by_key(|(name, _)| name.as_str());
+            roots.extend(sorted.into_iter().map(|(alias, dep)| RootDependency {
+                alias,
+                reference: dep.version(),
+                dev,
+                optional,
+            }));
+        }
+
+        PackageLock::from_dependency_graph(&roots, |alias, reference| {
+            self.resolve(alias, reference, registry)
+        })
+    }
+
+    /// Resolve a dependency edge `alias: reference` to its package.
+    fn resolve(&self, alias: &str, reference: &str, registry: &str) -> Option<ResolvedPackage<'_>> {
+        if let Some(path) = reference.strip_prefix("link:") {
+            return Some(ResolvedPackage {
+                key: reference.to_string(),
+                package: LockPackage {
+                    resolved: Some(format!("file:{path}")),
+                    ..Default::default()
+                },
+                dependencies: Vec::new(),
+            });
+        }
+
+        // Aliased (`npm:`) dependencies reference `name@version` directly.
+        let (name, full_version) = match parse_package_key(reference) {
+            Some((name, version)) if !reference.starts_with(|c: char| c.is_ascii_digit()) => {
+                (name, version)
+            }
+            _ => (alias.to_string(), reference.to_string()),
+        };
+        let version = strip_peer_suffix(&full_version).to_string();
+
+        let package = [
+            format!("{name}@{version}"),
+            format!("/{name}@{full_version}"),
+            format!("/{name}@{version}"),
+            format!("/{name}/{version}"),
+        ]
+        .iter()
+        .find_map(|key| self.packages.get(key));
+
+        let snapshot = self.snapshots.get(&format!("{name}@{full_version}"));
+        let (deps, optional_deps, optional) = match snapshot {
+            Some(s) => (&s.dependencies, &s.optional_dependencies, s.optional),
+            None => {
+                let p = package?;
+                (&p.dependencies, &p.optional_dependencies, p.optional)
+            }
+        };
+        let mut dependencies: Vec<(&str, &str)> = deps
+            .iter()
+            .chain(optional_deps.iter())
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        dependencies.sort();
+
+        let name = package.and_then(|p| p.name.clone()).unwrap_or(name);
+        let version = package.and_then(|p| p.version.clone()).unwrap_or(version);
+        let resolution = package.map(|p| &p.resolution);
+        let resolved = resolution
+            .and_then(|r| r.tarball.clone())
+            .unwrap_or_else(|| registry_tarball_url(registry, &name, &version));
+        let to_json = |v: &Option<Vec<String>>| v.as_ref().map(|list| serde_json::json!(list));
+
+        let package = LockPackage {
+            name: (name != alias).then(|| name.clone()),
+            version: Some(version.clone()),
+            resolved: Some(resolved),
+            integrity: resolution.and_then(|r| r.integrity.clone()),
+            dependencies: (!dependencies.is_empty()).then(|| {
+                dependencies
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), strip_peer_suffix(v).to_string()))
+                    .collect()
+            }),
+            optional: (optional == Some(true)).then_some(true),
+            os: package.and_then(|p| to_json(&p.os)),
+            cpu: package.and_then(|p| to_json(&p.cpu)),
+            ..Default::default()
+        };
+
+        Some(ResolvedPackage {
+            key: format!("{name}@{version}"),
+            package,
+            dependencies,
+        })
+    }
+}
+
+/// Drop a peer-dependency suffix: `18.2.0(react@18.2.0)` → `18.2.0`.
+fn strip_peer_suffix(version: &str) -> &str {
+    version.split_once('(').map_or(version, |(v, _)| v)
+}
+
+/// Split a package key into name and version.
+///
+/// Handles `name@1.0.0` (v9), `/name@1.0.0(peer@1.0.0)` (v6) and
+/// `/name/1.0.0` (v5), including scoped names. The version keeps any
+/// peer suffix.
+fn parse_package_key(key: &str) -> Option<(String, String)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let (head, peers) = match key.find('(') {
+        Some(i) => key.split_at(i),
+        None => (key, ""),
+    };
+    if let Some(at) = head.get(1..).and_then(|rest| rest.find('@')) {
+        let at = at + 1;
+        return Some((
+            head[..at].to_string(),
+            format!("{}{peers}", &head[at + 1..]),
+        ));
+    }
+    let (name, version) = head.rsplit_once('/')?;
+    Some((name.to_string(), format!("{version}{peers}")))
+}
+
+#[cfg(test)]
+mod tests {
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const V9: &str = r#"
+lockfileVersion: '9.0'
+
+importers:
+  .:
+    dependencies:
+      react-dom:
+        specifier: ^18.2.0
+        version: 18.2.0(react@18.2.0)
+      '@babel/runtime':
+        specifier: ^7.0.0
+        version: 7.24.0
+    devDependencies:
+      loose-envify:
+        specifier: ^1.0.0
+        version: 1.0.0
+
+packages:
+  react-dom@18.2.0:
+    resolution: {integrity: sha512-dom}
+  react@18.2.0:
+    resolution: {integrity: sha512-react}
+  loose-envify@1.4.0:
+    resolution: {integrity: sha512-le14}
+  loose-envify@1.0.0:
+    resolution: {integrity: sha512-le10}
+  '@babel/runtime@7.24.0':
+    resolution: {integrity: sha512-babel}
+
+snapshots:
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      react: 18.2.0
+  react@18.2.0:
+    dependencies:
+      loose-envify: 1.4.0
+  loose-envify@1.4.0: {}
+  loose-envify@1.0.0: {}
+  '@babel/runtime@7.24.0': {}
+"#;
+
+    const V6: &str = r#"
+lockfileVersion: '6.0'
+
+dependencies:
+  react:
+    specifier: ^18.2.0
+    version: 18.2.0
+
+packages:
+  /react@18.2.0:
+    resolution: {integrity: sha512-react}
+    dependencies:
+      loose-envify: 1.4.0
+    dev: false
+  /loose-envify@1.4.0:
+    resolution: {integrity: sha512-le14, tarball: https://mirror.example.com/le.tgz}
+    dev: false
+"#;
+
+    #[wasm_bindgen_test]
+    fn test_parse_package_key() {
+        assert_eq!(
+            parse_package_key("react@18.2.0"),
+            Some(("react".into(), "18.2.0".into()))
+        );
+        assert_eq!(
+            parse_package_key("/@babel/core@7.0.0(supports-color@5.0.0)"),
+            Some(("@babel/core".into(), "7.0.0(supports-color@5.0.0)".into()))
+        );
+        assert_eq!(
+            parse_package_key("/lodash/4.17.21"),
+            Some(("lodash".into(), "4.17.21".into()))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_pnpm_v9_to_package_lock() {
+        let lock = PnpmLock::from_yaml(V9).unwrap().to_package_lock();
+
+        let react_dom = &lock.packages["node_modules/react-dom"];
+        assert_eq!(react_dom.version.as_deref(), Some("18.2.0"));
+        assert_eq!(react_dom.integrity.as_deref(), Some("sha512-dom"));
+        assert_eq!(
+            react_dom.resolved.as_deref(),
+            Some("https://registry.npmjs.org/react-dom/-/react-dom-18.2.0.tgz")
+        );
+
+        let babel = &lock.packages["node_modules/@babel/runtime"];
+        assert_eq!(
+            babel.resolved.as_deref(),
+            Some("https://registry.npmjs.org/@babel/runtime/-/runtime-7.24.0.tgz")
+        );
+
+        // Direct dev dependency owns the top level; react's conflicting
+        // transitive dependency is nested.
+        let top = &lock.packages["node_modules/loose-envify"];
+        assert_eq!(top.version.as_deref(), Some("1.0.0"));
+        assert_eq!(top.dev, Some(true));
+        let nested = &lock.packages["node_modules/react/node_modules/loose-envify"];
+        assert_eq!(nested.version.as_deref(), Some("1.4.0"));
+        assert_eq!(nested.dev, None);
+
+        assert!(lock.packages.contains_key(""));
+        assert_eq!(lock.packages.len(), 6);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_pnpm_v6_to_package_lock() {
+        let lock = PnpmLock::from_yaml(V6).unwrap().to_package_lock();
+
+        let react = &lock.packages["node_modules/react"];
+        assert_eq!(react.integrity.as_deref(), Some("sha512-react"));
+
+        // Transitive dependency hoisted; explicit tarball URL kept as-is
+        let le = &lock.packages["node_modules/loose-envify"];
+        assert_eq!(le.version.as_deref(), Some("1.4.0"));
+        assert_eq!(
+            le.resolved.as_deref(),
+            Some("https://mirror.example.com/le.tgz")
+        );
+    }
+}
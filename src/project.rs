@@ -1,17 +1,32 @@
 //! Central project struct that owns all state.
 
-use std::io::Result;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 use bytes::Bytes;
+use futures::stream::{self, StreamExt};
 use tokio_fs_ext::DirEntry;
 
 use crate::config::Config;
 use crate::error::OpfsError;
-use crate::fuse_fs::FuseFs;
-use crate::package_lock::PackageLock;
-use crate::package_manager::{self, InstallOptions};
-use crate::store::Store;
+use crate::fuse_fs::{FuseCacheStats, FuseFs, FuseLinkInfo};
+use crate::install_log::{self, InstallLogEntry};
+use crate::package_lock::{LockfileFormat, PackageLock, is_valid_package_name};
+use crate::package_manager::{self, InstallCheck, InstallOptions, InstallReport, local_source_dir};
+use crate::pnpm_lock::PnpmLock;
+use crate::registry::RegistryConfig;
+use crate::store::{self, InstalledPackage, PackageStorePaths, PruneOptions, PruneReport, Store};
+use crate::yarn_lock::YarnLock;
+
+/// Max concurrent metadata lookups in [`OpfsProject::stat_all`].
+const STAT_CONCURRENCY: usize = 16;
+
+/// Registry settings picked up by [`OpfsProject::install_from_lock_file`].
+const NPMRC_FILE: &str = ".npmrc";
 
 /// The main API entry point for opfs-project.
 ///
@@ -21,6 +36,9 @@ pub struct OpfsProject {
     config: Config,
     fuse_fs: FuseFs,
     store: Store,
+    /// Bumped to start or stop the fuse-link watcher; a watcher runs while
+    /// this still holds the value it started with
+    fuse_watch_generation: AtomicU64,
 }
 
 impl Default for OpfsProject {
@@ -38,6 +56,7 @@ impl OpfsProject {
             config,
             fuse_fs,
             store,
+            fuse_watch_generation: AtomicU64::new(0),
         }
     }
 
@@ -77,7 +96,12 @@ impl OpfsProject {
     pub async fn read(&self, path: impl AsRef<Path>) -> Result<Bytes> {
         let prepared = self.prepare_path(path.as_ref());
 
-        if let Some(content) = self.fuse_fs.try_read(&prepared).await? {
+        let strategy = self.config.read_dir_strategy;
+        if let Some(content) = self
+            .fuse_fs
+            .try_read_with_strategy(&prepared, strategy)
+            .await?
+        {
             return Ok(content);
         }
 
@@ -85,39 +109,814 @@ impl OpfsProject {
         Ok(Bytes::from(raw))
     }
 
-    /// Read directory contents, transparently merging fuse-link entries.
-    pub async fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<DirEntry>> {
+    /// Read a UTF-8 file into a `String`, transparently resolving fuse links.
+    ///
+    /// Invalid UTF-8 is reported as [`ErrorKind::InvalidData`].
+    pub async fn read_to_string(&self, path: impl AsRef<Path>) -> Result<String> {
+        let content = self.read(path).await?;
+        String::from_utf8(content.to_vec()).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Read and deserialize a JSON file, transparently resolving fuse links.
+    ///
+    /// JSON errors are reported as [`ErrorKind::InvalidData`].
+    pub async fn read_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<T> {
+        let content = self.read(path).await?;
+        serde_json::from_slice(&content).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Read the raw `package.json` of the package installed at
+    /// `install_path` (e.g. `node_modules/react`).
+    ///
+    /// A missing manifest is reported as [`ErrorKind::NotFound`] naming
+    /// the package directory.
+    pub async fn get_package_json_str(&self, install_path: impl AsRef<Path>) -> Result<String> {
+        let install_path = install_path.as_ref();
+        self.read_to_string(install_path.join("package.json"))
+            .await
+            .map_err(|e| package_json_error(install_path, e))
+    }
+
+    /// Read and parse the `package.json` of the package installed at
+    /// `install_path`; see [`get_package_json_str`](Self::get_package_json_str).
+    pub async fn get_package_json(
+        &self,
+        install_path: impl AsRef<Path>,
+    ) -> Result<serde_json::Value> {
+        let install_path = install_path.as_ref();
+        self.read_json(install_path.join("package.json"))
+            .await
+            .map_err(|e| package_json_error(install_path, e))
+    }
+
+    /// Serialize `value` as pretty-printed JSON and write it to `path`,
+    /// transparently resolving fuse links.
+    pub async fn write_json<T: serde::Serialize>(
+        &self,
+        path: impl AsRef<Path>,
+        value: &T,
+    ) -> Result<()> {
+        let json =
+            serde_json::to_vec_pretty(value).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        self.write(path, json).await
+    }
+
+    /// Write file content, transparently resolving fuse links.
+    ///
+    /// Writes under a linked package (e.g. `node_modules/react/dist/x.js`)
+    /// are copy-on-write: the file lands next to the package's `fuse.link`
+    /// and shadows the store copy, which other projects share and is never
+    /// modified. Reading it back needs the `Overlay`
+    /// [`Config::read_dir_strategy`].
+    pub async fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
         let prepared = self.prepare_path(path.as_ref());
 
-        if let Some(entries) = self.fuse_fs.try_read_dir(&prepared).await? {
-            return Ok(entries);
+        if self.fuse_fs.try_write(&prepared, contents.as_ref()).await? {
+            return Ok(());
         }
 
-        tokio_fs_ext::read_dir(&prepared).await?.collect()
+        tokio_fs_ext::write(&prepared, contents).await
+    }
+
+    /// Read directory contents, transparently merging fuse-link entries
+    /// as set by [`Config::read_dir_strategy`].
+    ///
+    /// Entries are sorted by file name (code-point order), whatever order
+    /// the browser enumerates them in.
+    pub async fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<DirEntry>> {
+        let prepared = self.prepare_path(path.as_ref());
+
+        let listed = self
+            .fuse_fs
+            .try_read_dir_with_strategy(&prepared, self.config.read_dir_strategy)
+            .await?;
+        let mut entries = match listed {
+            Some(entries) => entries,
+            None => tokio_fs_ext::read_dir(&prepared)
+                .await?
+                .collect::<Result<_>>()?,
+        };
+        entries.sort_by_key(|entry| entry.file_name());
+        Ok(entries)
+    }
+
+    /// Every file under `path`, following fuse links like
+    /// [`read_dir`](Self::read_dir). Directories are descended at most
+    /// `max_depth` levels: `0` lists only the files directly in `path`.
+    ///
+    /// Paths are sorted.
+    pub async fn walk_dir(&self, path: impl AsRef<Path>, max_depth: usize) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .read_dir_recursive(path, max_depth)
+            .await?
+            .into_iter()
+            .filter(|(_, meta)| !meta.is_dir())
+            .map(|(path, _)| path)
+            .collect())
+    }
+
+    /// Every file and directory under `path` with its metadata, as
+    /// returned by [`metadata`](Self::metadata) — so entries inside fuse
+    /// links report their real type. Descends `max_depth` levels like
+    /// [`walk_dir`](Self::walk_dir).
+    ///
+    /// Paths are sorted. The walk uses an explicit stack, so deeply nested
+    /// packages cannot overflow the call stack.
+    pub async fn read_dir_recursive(
+        &self,
+        path: impl AsRef<Path>,
+        max_depth: usize,
+    ) -> Result<Vec<(PathBuf, tokio_fs_ext::Metadata)>> {
+        let mut entries = Vec::new();
+        let mut stack = vec![(self.prepare_path(path.as_ref()), 0)];
+        while let Some((dir, depth)) = stack.pop() {
+            for entry in self.read_dir(&dir).await? {
+                let path = dir.join(entry.file_name());
+                let meta = self.metadata(&path).await?;
+                if meta.is_dir() && depth < max_depth {
+                    stack.push((path.clone(), depth + 1));
+                }
+                entries.push((path, meta));
+            }
+        }
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(entries)
     }
 
     /// Get file/directory metadata, transparently resolving fuse links.
     pub async fn metadata(&self, path: impl AsRef<Path>) -> Result<tokio_fs_ext::Metadata> {
         let prepared = self.prepare_path(path.as_ref());
 
-        if let Some(meta) = self.fuse_fs.try_metadata(&prepared).await? {
+        let strategy = self.config.read_dir_strategy;
+        if let Some(meta) = self
+            .fuse_fs
+            .try_metadata_with_strategy(&prepared, strategy)
+            .await?
+        {
             return Ok(meta);
         }
 
         tokio_fs_ext::metadata(&prepared).await
     }
 
+    /// Like [`write`](Self::write), but never leaves `path` half-written if
+    /// the worker dies mid-write; see [`store::write_atomic`].
+    pub async fn write_atomic(
+        &self,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let prepared = self.prepare_path(path.as_ref());
+        if self.fuse_fs.try_real_path(&prepared).await?.is_some()
+            && let Some(parent) = prepared.parent()
+        {
+            tokio_fs_ext::create_dir_all(parent).await?;
+        }
+        store::write_atomic(&prepared, contents.as_ref()).await
+    }
+
+    /// Copy a file, transparently resolving fuse links on the source.
+    ///
+    /// Returns the number of bytes copied.
+    pub async fn copy(&self, from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<u64> {
+        let from = self.prepare_path(from.as_ref());
+        let to = self.prepare_path(to.as_ref());
+
+        let strategy = self.config.read_dir_strategy;
+        if let Some(content) = self.fuse_fs.try_read_with_strategy(&from, strategy).await? {
+            tokio_fs_ext::write(&to, &content).await?;
+            return Ok(content.len() as u64);
+        }
+
+        tokio_fs_ext::copy(&from, &to).await
+    }
+
+    /// Rename a file or directory.
+    ///
+    /// Renaming a fuse-linked package directory (e.g. `node_modules/old` →
+    /// `node_modules/new`) moves its link instead of the store contents.
+    pub async fn rename(&self, from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+        let from = self.prepare_path(from.as_ref());
+        let to = self.prepare_path(to.as_ref());
+
+        if self.fuse_fs.try_rename(&from, &to).await? {
+            return Ok(());
+        }
+
+        tokio_fs_ext::rename(&from, &to).await
+    }
+
+    /// Create a directory and its parents, transparently resolving fuse
+    /// links.
+    ///
+    /// Directories under a linked package (e.g. `node_modules/a/dist`) are
+    /// created as real entries next to `fuse.link`, like files written with
+    /// [`write`](Self::write); the shared store is left as is.
+    pub async fn create_dir_all(&self, path: impl AsRef<Path>) -> Result<()> {
+        let prepared = self.prepare_path(path.as_ref());
+
+        if self.fuse_fs.try_create_dir_all(&prepared).await? {
+            return Ok(());
+        }
+
+        tokio_fs_ext::create_dir_all(&prepared).await
+    }
+
+    /// Remove a file, transparently resolving fuse links.
+    ///
+    /// Under a linked package only a real (copy-on-write) file is removed;
+    /// the shared store is never changed, so removing a file that only
+    /// exists there fails with `PermissionDenied`.
+    pub async fn remove_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let prepared = self.prepare_path(path.as_ref());
+
+        if self.fuse_fs.try_remove_file(&prepared).await? {
+            return Ok(());
+        }
+
+        tokio_fs_ext::remove_file(&prepared).await
+    }
+
+    /// Remove a directory and its contents, transparently resolving fuse
+    /// links.
+    ///
+    /// Removing a linked package directory (e.g. `node_modules/lodash`)
+    /// removes its link and keeps the shared store copy; see
+    /// [`FuseFs::remove_fuse_link`]. Inside a linked package the same
+    /// rules as [`remove_file`](Self::remove_file) apply.
+    pub async fn remove_dir_all(&self, path: impl AsRef<Path>) -> Result<()> {
+        let prepared = self.prepare_path(path.as_ref());
+
+        if self.fuse_fs.try_remove_dir_all(&prepared).await? {
+            return Ok(());
+        }
+
+        tokio_fs_ext::remove_dir_all(&prepared).await?;
+        self.fuse_fs.evict_links_under(&prepared);
+        Ok(())
+    }
+
+    /// Remove the fuse link of the package at `path` (e.g.
+    /// `node_modules/lodash`) without touching its store copy; see
+    /// [`FuseFs::unlink`].
+    pub async fn unlink_fuse(&self, path: impl AsRef<Path>) -> Result<()> {
+        let prepared = self.prepare_path(path.as_ref());
+        self.fuse_fs.unlink(&prepared).await
+    }
+
+    /// Check whether a path exists, transparently resolving fuse links.
+    ///
+    /// Returns `Ok(false)` on [`ErrorKind::NotFound`]; other errors are
+    /// propagated rather than reported as "missing".
+    pub async fn exists(&self, path: impl AsRef<Path>) -> Result<bool> {
+        match self.metadata(path).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get metadata for many paths concurrently.
+    ///
+    /// Each lookup behaves exactly like [`metadata`](Self::metadata).
+    /// Results are returned in the same order as `paths`.
+    pub async fn stat_all<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+    ) -> Vec<Result<tokio_fs_ext::Metadata>> {
+        stream::iter(paths.iter().map(|p| self.metadata(p)))
+            .buffered(STAT_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Check, without I/O, whether `path` is served by the fuse layer rather
+    /// than plain OPFS.
+    ///
+    /// Answers from the fuse-link cache only; see
+    /// [`FuseFs::is_cached_link_path`].
+    pub fn is_virtual_path(&self, path: impl AsRef<Path>) -> bool {
+        let prepared = self.prepare_path(path.as_ref());
+        self.fuse_fs.is_cached_link_path(&prepared)
+    }
+
+    /// Where a fuse-linked `path` lives in the store, e.g.
+    /// `node_modules/react/index.js` → `/stores/react/-/react-18.2.0/index.js`,
+    /// or `None` when `path` is not under a fuse link. Reads at most the
+    /// `fuse.link` file, never the target.
+    pub async fn get_fuse_link_target(&self, path: impl AsRef<Path>) -> Result<Option<PathBuf>> {
+        let prepared = self.prepare_path(path.as_ref());
+        self.fuse_fs.try_real_path(&prepared).await
+    }
+
+    /// Every `fuse.link` file under `root` and its target, read from disk.
+    pub async fn scan_fuse_links(&self, root: impl AsRef<Path>) -> Result<Vec<FuseLinkInfo>> {
+        let root = self.prepare_path(root.as_ref());
+        self.fuse_fs.scan_links(&root).await
+    }
+
+    /// Repair fuse links under `root` whose target directory has gone
+    /// missing, re-pointing them at the same package in the configured
+    /// store. Returns the number of links repaired.
+    pub async fn repair_broken_links(&self, root: impl AsRef<Path>) -> Result<usize> {
+        let root = self.prepare_path(root.as_ref());
+        self.fuse_fs
+            .repair_broken_links(&root, &self.config.store_root)
+            .await
+    }
+
     // ── package management ───────────────────────────────────────────
 
+    /// Registries packages are fetched from (initially [`Config::registry`]).
+    pub fn registry_config(&self) -> RegistryConfig {
+        self.store.registry_config()
+    }
+
+    /// Replace the registry configuration used by later installs.
+    pub fn set_registry_config(&self, registry: RegistryConfig) {
+        self.store.set_registry_config(registry);
+    }
+
+    /// Send `Authorization: Bearer <token>` with downloads from
+    /// `registry_host` (e.g. `npm.example.com`).
+    pub fn set_registry_auth_token(&self, registry_host: &str, token: &str) {
+        let mut registry = self.registry_config();
+        registry.set_auth_token(registry_host, token);
+        self.set_registry_config(registry);
+    }
+
+    /// Route downloads through a CORS proxy, for registries that cannot be
+    /// fetched from the browser directly; see
+    /// [`RegistryConfig::set_cors_proxy`].
+    pub fn set_cors_proxy(&self, proxy: &str) {
+        let mut registry = self.registry_config();
+        registry.set_cors_proxy(proxy);
+        self.set_registry_config(registry);
+    }
+
+    /// Whether auth tokens are sent through the CORS proxy; see
+    /// [`RegistryConfig::set_cors_proxy_auth`].
+    pub fn set_cors_proxy_auth(&self, forward: bool) {
+        let mut registry = self.registry_config();
+        registry.set_cors_proxy_auth(forward);
+        self.set_registry_config(registry);
+    }
+
+    /// Stop routing downloads through the CORS proxy.
+    pub fn clear_cors_proxy(&self) {
+        let mut registry = self.registry_config();
+        registry.clear_cors_proxy();
+        self.set_registry_config(registry);
+    }
+
+    /// Read registry settings from an `.npmrc` file and merge them over the
+    /// current registry configuration.
+    pub async fn load_npmrc(&self, path: impl AsRef<Path>) -> std::result::Result<(), OpfsError> {
+        let content = self.read(path).await?;
+        let npmrc = RegistryConfig::from_npmrc(&String::from_utf8_lossy(&content));
+        let mut registry = self.registry_config();
+        registry.merge(npmrc);
+        self.set_registry_config(registry);
+        Ok(())
+    }
+
     /// Install packages from a parsed `PackageLock`.
+    ///
+    /// Fails with the first error unless
+    /// [`InstallOptions::continue_on_error`] is set, in which case failures
+    /// are listed in the returned [`InstallReport`].
     pub async fn install(
         &self,
         lock: &PackageLock,
         opts: &InstallOptions,
-    ) -> std::result::Result<(), OpfsError> {
+    ) -> std::result::Result<InstallReport, OpfsError> {
         package_manager::install(self, lock, opts).await
     }
 
+    /// Write `node_modules/.bin` shims for the executables of every
+    /// package in `lock`; run after [`install`](Self::install). Returns the
+    /// number of shims written.
+    pub async fn install_bin_links(
+        &self,
+        lock: &PackageLock,
+    ) -> std::result::Result<usize, OpfsError> {
+        package_manager::install_bin_links(self, lock).await
+    }
+
+    /// Install one package at `install_path` (e.g. `node_modules/lodash`,
+    /// relative to the CWD) without a lockfile: fetch its tgz into the
+    /// store, extract it and create the fuse link.
+    ///
+    /// `install_path` must lie under `node_modules/`, so a typo cannot
+    /// turn an arbitrary directory into a link.
+    pub async fn install_package(
+        &self,
+        name: &str,
+        version: &str,
+        tgz_url: &str,
+        integrity: Option<&str>,
+        shasum: Option<&str>,
+        install_path: &str,
+    ) -> std::result::Result<(), OpfsError> {
+        let valid = install_path
+            .strip_prefix("node_modules/")
+            .is_some_and(|rest| !rest.is_empty() && !rest.split('/').any(|c| c == ".."));
+        if !valid {
+            return Err(OpfsError::Other(format!(
+                "install path must be under node_modules/: {install_path}"
+            )));
+        }
+        package_manager::install_package(
+            self,
+            name,
+            version,
+            tgz_url,
+            integrity,
+            shasum,
+            install_path,
+        )
+        .await
+    }
+
+    /// Bytes an install of `lock` would download: tarballs already in the
+    /// store count as 0, the rest use the lockfile's `size` hint or else
+    /// the registry's `Content-Length` (see [`Store::download_size`]).
+    ///
+    /// Unlike [`PackageLock::total_download_size`] this makes network
+    /// requests, at most `max_concurrent_downloads` at a time.
+    pub async fn estimate_download_size(
+        &self,
+        lock: &PackageLock,
+    ) -> std::result::Result<u64, OpfsError> {
+        let registry = self.registry_config();
+        let mut tarballs: HashMap<String, (String, Option<u64>)> = HashMap::new();
+        for (path, pkg) in lock.packages.iter().filter(|(p, _)| !p.is_empty()) {
+            let name = pkg.get_name(path);
+            if pkg.local_path().is_some() || lock.bundling_parent(path).is_some() {
+                continue;
+            }
+            let version = pkg.get_version();
+            let Some(url) = registry.tarball_url(&name, &version, pkg.resolved.as_deref()) else {
+                continue;
+            };
+            tarballs
+                .entry(url)
+                .or_insert_with(|| (name.into_owned(), pkg.size));
+        }
+
+        let store = &self.store;
+        let sizes: Vec<_> =
+            stream::iter(tarballs.into_iter().map(|(url, (name, hint))| async move {
+                if store.is_cached(&name, &url).await {
+                    return Ok::<_, OpfsError>(0);
+                }
+                match hint {
+                    Some(size) => Ok(size),
+                    None => Ok(store.download_size(&url).await?.unwrap_or(0)),
+                }
+            }))
+            .buffer_unordered(self.config.max_concurrent_downloads.max(1))
+            .collect()
+            .await;
+        sizes
+            .into_iter()
+            .try_fold(0u64, |total, size| Ok(total.saturating_add(size?)))
+    }
+
+    /// Where a package from `tgz_url` is (or will be) stored when
+    /// installed at `path_key` (e.g. `node_modules/lodash`).
+    pub fn get_store_path_for(
+        &self,
+        name: &str,
+        tgz_url: &str,
+        path_key: &str,
+    ) -> PackageStorePaths {
+        let path_key = self.prepare_path(Path::new(path_key));
+        self.store.package_paths(name, tgz_url, &path_key)
+    }
+
+    /// Every package extracted into the store, e.g. for a cache browser UI.
+    pub async fn list_store_packages(
+        &self,
+    ) -> std::result::Result<Vec<InstalledPackage>, OpfsError> {
+        self.store.list_packages().await
+    }
+
+    /// Files of the package stored at `tgz_path` (see
+    /// [`get_store_path_for`](Self::get_store_path_for)), or `None` when
+    /// it has not been extracted yet.
+    pub async fn cached_file_list(
+        &self,
+        tgz_path: impl AsRef<Path>,
+    ) -> Result<Option<Vec<String>>> {
+        self.fuse_fs.extracted_file_list(tgz_path.as_ref()).await
+    }
+
+    /// Remove every stored version of package `name` and drop cached fuse
+    /// links into it; see [`Store::remove_package`]. Returns the number of
+    /// bytes freed.
+    ///
+    /// `fuse.link` files pointing at the removed versions stay in place
+    /// and resolve again once the package is reinstalled. Fails when `name`
+    /// is not `name` or `@scope/name`.
+    pub async fn clear_package_cache(&self, name: &str) -> std::result::Result<u64, OpfsError> {
+        let freed_bytes = self.store.remove_package(name).await?;
+        self.fuse_fs.evict_links_into(&self.store.package_dir(name));
+        Ok(freed_bytes)
+    }
+
+    /// Check each package of `lock` after an install: its fuse link is
+    /// readable, its store directory exists and was completely extracted,
+    /// and with `verify_integrity` its stored tgz matches the lockfile
+    /// hashes. Optional packages that were never linked are not reported.
+    ///
+    /// Pass the options `lock` was installed with: packages they skip
+    /// (`omit`, platform mismatches) are not checked.
+    pub async fn verify_installed_packages(
+        &self,
+        lock: &PackageLock,
+        verify_integrity: bool,
+        opts: &InstallOptions,
+    ) -> Vec<InstallCheck> {
+        package_manager::verify_installed(self, lock, verify_integrity, opts).await
+    }
+
+    /// Check that the stored copy of a package is complete and intact; see
+    /// [`Store::verify_cached`].
+    pub async fn verify_store_integrity(
+        &self,
+        name: &str,
+        tgz_url: &str,
+        integrity: Option<&str>,
+        shasum: Option<&str>,
+    ) -> std::result::Result<bool, OpfsError> {
+        self.store
+            .verify_cached(name, tgz_url, integrity, shasum)
+            .await
+    }
+
+    /// Remove packages from the store that `lock` no longer references,
+    /// e.g. after dependencies were upgraded or dropped.
+    ///
+    /// Every package in `lock` is kept, whatever install options it was
+    /// installed with.
+    ///
+    /// **Warning:** the store root is shared by every project using the
+    /// same [`Config::store_root`]; packages only another project's lock
+    /// references are removed too and must be fetched again by it.
+    pub async fn prune_store(
+        &self,
+        lock: &PackageLock,
+        opts: PruneOptions,
+    ) -> std::result::Result<PruneReport, OpfsError> {
+        let registry = self.registry_config();
+        let cwd = self.cwd();
+        let keep = lock
+            .packages
+            .iter()
+            .filter(|(path, _)| !path.is_empty())
+            .filter_map(|(path, pkg)| {
+                let name = pkg.get_name(path);
+                if let Some(local_path) = pkg.local_path() {
+                    let source_dir = local_source_dir(&cwd, local_path);
+                    let copy = self.store.local_package_dir(&name, &source_dir);
+                    return Some(copy.with_extension("tgz"));
+                }
+                let tgz_url =
+                    registry.tarball_url(&name, &pkg.get_version(), pkg.resolved.as_deref())?;
+                Some(self.store.tgz_path(&name, &tgz_url))
+            })
+            .collect();
+        self.store.prune(&keep, opts).await
+    }
+
+    /// Install the project in `cwd` from its lockfile.
+    ///
+    /// Sets the CWD to `cwd`, loads its `.npmrc` if present (see
+    /// [`load_npmrc`](Self::load_npmrc)), detects the lockfile by name (see
+    /// [`LockfileFormat::ALL`] for the order) and installs from it.
+    /// `pnpm-lock.yaml` and `yarn.lock` are converted to an npm-style tree
+    /// first; for `yarn.lock` the root dependencies come from `package.json`.
+    pub async fn install_from_lock_file(
+        &self,
+        cwd: impl AsRef<Path>,
+        opts: &InstallOptions,
+    ) -> std::result::Result<InstallReport, OpfsError> {
+        self.set_cwd(cwd);
+
+        let mut detected = None;
+        for format in LockfileFormat::ALL {
+            if tokio_fs_ext::metadata(self.prepare_path(Path::new(format.file_name())))
+                .await
+                .is_ok_and(|m| m.is_file())
+            {
+                detected = Some(format);
+                break;
+            }
+        }
+        let format = detected.ok_or_else(|| {
+            OpfsError::NotFound(format!("no lockfile in {}", self.cwd().display()))
+        })?;
+
+        if self.exists(NPMRC_FILE).await? {
+            self.load_npmrc(NPMRC_FILE).await?;
+        }
+
+        let lock = self
+            .load_lock_file(Path::new(format.file_name()), format)
+            .await?;
+        self.install(&lock, opts).await
+    }
+
+    /// Install into the current CWD from the lockfile at `lock_file_path`,
+    /// read through the fuse layer like [`read`](Self::read).
+    ///
+    /// The format is taken from the file name; other names are parsed as
+    /// `package-lock.json`. For `yarn.lock` the root dependencies come from
+    /// the `package.json` next to it. Unlike
+    /// [`install_from_lock_file`](Self::install_from_lock_file), neither
+    /// the CWD nor the registry settings are changed.
+    pub async fn install_from_file(
+        &self,
+        lock_file_path: impl AsRef<Path>,
+        opts: &InstallOptions,
+    ) -> std::result::Result<InstallReport, OpfsError> {
+        let path = lock_file_path.as_ref();
+        let format = LockfileFormat::from_path(path).unwrap_or(LockfileFormat::Npm);
+        let lock = self.load_lock_file(path, format).await?;
+        self.install(&lock, opts).await
+    }
+
+    /// Read and parse the lockfile at `path` as `format`, converting it to
+    /// an npm-style tree.
+    async fn load_lock_file(
+        &self,
+        path: &Path,
+        format: LockfileFormat,
+    ) -> std::result::Result<PackageLock, OpfsError> {
+        let bytes = self.read(path).await?;
+        let parse_error =
+            |e: &dyn std::fmt::Display| OpfsError::Other(format!("parse {}: {e}", path.display()));
+        Ok(match format {
+            LockfileFormat::Npm => PackageLock::from_bytes(&bytes).map_err(|e| parse_error(&e))?,
+            LockfileFormat::Pnpm => PnpmLock::from_yaml(&String::from_utf8_lossy(&bytes))
+                .map_err(|e| parse_error(&e))?
+                .to_package_lock(),
+            LockfileFormat::Yarn => {
+                let package_json_path = path.with_file_name("package.json");
+                let package_json: serde_json::Value = self.read_json(package_json_path).await?;
+                String::from_utf8_lossy(&bytes)
+                    .parse::<YarnLock>()
+                    .map_err(|e| parse_error(&e))?
+                    .to_package_lock(&package_json)
+            }
+        })
+    }
+
+    /// Build an [import map] for the top-level packages of `lock`, so the
+    /// browser can load them as native ES modules without a bundler.
+    ///
+    /// Each package maps to `<base_url>/node_modules/<name>/<entry>`, where
+    /// `<entry>` is the `module` or else `main` field of its installed
+    /// `package.json` (`index.js` if neither is set); `<name>/` maps to the
+    /// package directory for subpath imports. Packages that are not
+    /// installed (e.g. omitted) are left out.
+    ///
+    /// [import map]: https://developer.mozilla.org/en-US/docs/Web/HTML/Element/script/type/importmap
+    pub async fn generate_import_map(&self, lock: &PackageLock, base_url: &str) -> Result<String> {
+        let base_url = base_url.trim_end_matches('/');
+        let mut imports = serde_json::Map::new();
+
+        for path in lock.packages.keys() {
+            let Some(name) = path.strip_prefix("node_modules/") else {
+                continue;
+            };
+            if name.contains("/node_modules/") {
+                continue;
+            }
+            let package_json: serde_json::Value =
+                match self.read_json(Path::new(path).join("package.json")).await {
+                    Ok(value) => value,
+                    Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                };
+            let entry = ["module", "main"]
+                .iter()
+                .find_map(|field| package_json[*field].as_str())
+                .unwrap_or("index.js");
+            let entry = entry.strip_prefix("./").unwrap_or(entry);
+
+            let package_url = format!("{base_url}/{path}/");
+            imports.insert(name.to_string(), format!("{package_url}{entry}").into());
+            imports.insert(format!("{name}/"), package_url.into());
+        }
+
+        let import_map = serde_json::json!({ "imports": imports });
+        serde_json::to_string_pretty(&import_map).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Read back every session recorded in the install log
+    /// (`.opfs-project-install.log` in the CWD), oldest first.
+    pub async fn read_install_log(&self) -> std::result::Result<Vec<InstallLogEntry>, OpfsError> {
+        install_log::read(&self.cwd().join(install_log::INSTALL_LOG_FILE)).await
+    }
+
+    /// Link a local development directory into `node_modules/<name>`,
+    /// `npm link`-style.
+    ///
+    /// Unlike installed packages, `target_dir` lives outside the store and
+    /// the link is marked as a dev link (see [`FuseLink::dev`](crate::fuse_fs::FuseLink::dev)).
+    /// `name` must be `name` or `@scope/name`.
+    pub async fn link_package(&self, name: &str, target_dir: impl AsRef<Path>) -> Result<()> {
+        if !is_valid_package_name(name) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid package name: {name:?}"),
+            ));
+        }
+        let target_dir = self.prepare_path(target_dir.as_ref());
+        if !tokio_fs_ext::metadata(&target_dir).await?.is_dir() {
+            return Err(OpfsError::Other(format!(
+                "link target is not a directory: {}",
+                target_dir.display()
+            ))
+            .into());
+        }
+
+        let dst = self.prepare_path(&Path::new("node_modules").join(name));
+        self.fuse_fs.create_dev_link(&target_dir, &dst).await
+    }
+
+    /// Resize the in-memory fuse-link cache (initially
+    /// [`Config::fuse_cache_max_entries`]), e.g. for memory-constrained
+    /// workers.
+    pub fn set_fuse_cache_max_entries(&self, max_entries: usize) {
+        self.fuse_fs.set_cache_max_entries(max_entries);
+    }
+
+    /// Entry count, capacity and hit/miss counters of the fuse-link cache.
+    pub fn fuse_cache_stats(&self) -> FuseCacheStats {
+        self.fuse_fs.cache_stats()
+    }
+
+    /// The fuse links currently cached, for debugging; see
+    /// [`scan_fuse_links`](Self::scan_fuse_links) for all links on disk.
+    pub fn cached_fuse_links(&self) -> Vec<FuseLinkInfo> {
+        self.fuse_fs.cached_links()
+    }
+
+    /// Drop every cached fuse link; links are re-read from disk on demand.
+    pub fn clear_fuse_cache(&self) {
+        self.fuse_fs.clear();
+    }
+
+    /// Drop the cached fuse links under `path` only (e.g. one package's
+    /// `node_modules/<name>` after a hot update), leaving the rest of the
+    /// cache warm.
+    pub fn invalidate_fuse_cache(&self, path: impl AsRef<Path>) {
+        let prepared = self.prepare_path(path.as_ref());
+        self.fuse_fs.evict_links_under(&prepared);
+    }
+
+    /// Every `interval_ms`, evict cached fuse links whose `fuse.link`
+    /// changed on disk (see [`FuseFs::evict_stale_links`]), so packages
+    /// reinstalled by another worker are picked up without a reload.
+    ///
+    /// The watcher runs on the current thread's event loop until
+    /// [`stop_watching_fuse_links`](Self::stop_watching_fuse_links) is
+    /// called, another watch is started, or the project is dropped.
+    pub fn watch_fuse_links(self: &Arc<Self>, interval_ms: u32) {
+        let generation = self.fuse_watch_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let project = Arc::downgrade(self);
+        let interval = Duration::from_millis(interval_ms.max(1).into());
+
+        wasm_bindgen_futures::spawn_local(async move {
+            loop {
+                wasmtimer::tokio::sleep(interval).await;
+                let Some(project) = project.upgrade() else {
+                    break;
+                };
+                if project.fuse_watch_generation.load(Ordering::Relaxed) != generation {
+                    break;
+                }
+                let evicted = project.fuse_fs.evict_stale_links().await;
+                if evicted > 0 {
+                    tracing::debug!("evicted {evicted} stale fuse links");
+                }
+            }
+        });
+    }
+
+    /// Stop the watcher started by
+    /// [`watch_fuse_links`](Self::watch_fuse_links), if any. It exits at
+    /// its next tick.
+    pub fn stop_watching_fuse_links(&self) {
+        self.fuse_watch_generation.fetch_add(1, Ordering::Relaxed);
+    }
+
     // ── accessors for internal subsystems ─────────────────────────────
 
     pub fn config(&self) -> &Config {
@@ -132,3 +931,402 @@ impl OpfsProject {
         &self.fuse_fs
     }
 }
+
+/// Name the package directory when its `package.json` is missing.
+fn package_json_error(install_path: &Path, e: Error) -> Error {
+    if e.kind() == ErrorKind::NotFound {
+        Error::new(
+            ErrorKind::NotFound,
+            format!("no package.json in {}", install_path.display()),
+        )
+    } else {
+        e
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    /// Empty `base` and a project working in it.
+    async fn project_at(base: &Path) -> OpfsProject {
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+        tokio_fs_ext::create_dir_all(base).await.unwrap();
+        let project = OpfsProject::default();
+        project.set_cwd(base);
+        project
+    }
+
+    /// [`project_at`] with `files` in a store copy at
+    /// `<base>/stores/pkg-1.0.0`, linked at `<base>/node_modules/<name>`.
+    /// Returns the project and the install path.
+    async fn linked_project(
+        base: &Path,
+        name: &str,
+        files: &[(&str, &str)],
+    ) -> (OpfsProject, PathBuf) {
+        let project = project_at(base).await;
+        let target = base.join("stores/pkg-1.0.0");
+        tokio_fs_ext::create_dir_all(&target).await.unwrap();
+        for (file, content) in files {
+            let path = target.join(file);
+            tokio_fs_ext::create_dir_all(path.parent().unwrap())
+                .await
+                .unwrap();
+            tokio_fs_ext::write(&path, content).await.unwrap();
+        }
+        let dst = base.join("node_modules").join(name);
+        project
+            .fuse_fs()
+            .create_fuse_link(&target, &dst)
+            .await
+            .unwrap();
+        (project, dst)
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_copy_across_fuse_link() {
+        let base = Path::new("/test_project_copy");
+        let (project, _) = linked_project(base, "pkg", &[("index.js", "module.exports = 1")]).await;
+
+        // Out of a linked package into plain OPFS
+        let copied = project
+            .copy(
+                base.join("node_modules/pkg/index.js"),
+                base.join("index.js"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(copied, 18);
+        assert_eq!(
+            tokio_fs_ext::read(base.join("index.js")).await.unwrap(),
+            b"module.exports = 1"
+        );
+
+        // Plain OPFS to plain OPFS
+        let copied = project
+            .copy(base.join("index.js"), base.join("copy.js"))
+            .await
+            .unwrap();
+        assert_eq!(copied, 18);
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_read_dir_is_sorted() {
+        let base = Path::new("/test_project_read_dir_sorted");
+        let (project, dst) = linked_project(base, "pkg", &[("z.js", ""), ("index.js", "")]).await;
+        let names = |entries: Vec<DirEntry>| {
+            entries
+                .iter()
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        tokio_fs_ext::create_dir_all(base.join("plain"))
+            .await
+            .unwrap();
+        for name in ["b.js", "a.js", "C.js"] {
+            tokio_fs_ext::write(base.join("plain").join(name), b"")
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            names(project.read_dir(base.join("plain")).await.unwrap()),
+            ["C.js", "a.js", "b.js"]
+        );
+
+        // Linked package with a local file merged into the listing
+        tokio_fs_ext::write(dst.join("local.js"), b"")
+            .await
+            .unwrap();
+        assert_eq!(
+            names(project.read_dir(&dst).await.unwrap()),
+            ["index.js", "local.js", "z.js"]
+        );
+
+        // Listing the link target only
+        use crate::fuse_fs::ReadDirStrategy;
+        let first_match = OpfsProject::new(Config {
+            read_dir_strategy: ReadDirStrategy::FirstMatch,
+            ..Default::default()
+        });
+        assert_eq!(
+            names(first_match.read_dir(&dst).await.unwrap()),
+            ["index.js", "z.js"]
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_get_fuse_link_target() {
+        let base = Path::new("/test_project_link_target");
+        let (project, dst) = linked_project(base, "pkg", &[]).await;
+        let target = base.join("stores/pkg-1.0.0");
+
+        assert_eq!(
+            project
+                .get_fuse_link_target(dst.join("lib/index.js"))
+                .await
+                .unwrap(),
+            Some(target.join("lib/index.js"))
+        );
+        assert_eq!(
+            project
+                .get_fuse_link_target(base.join("src/index.js"))
+                .await
+                .unwrap(),
+            None
+        );
+
+        // Invalidated links are re-read from disk
+        project.invalidate_fuse_cache(base.join("src"));
+        assert!(project.is_virtual_path(dst.join("index.js")));
+        project.invalidate_fuse_cache(&dst);
+        assert!(!project.is_virtual_path(dst.join("index.js")));
+        assert_eq!(
+            project.get_fuse_link_target(&dst).await.unwrap(),
+            Some(target)
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_link_package() {
+        let base = Path::new("/test_project_link_package");
+        let project = project_at(base).await;
+        let dev_dir = base.join("dev/ui");
+        tokio_fs_ext::create_dir_all(&dev_dir).await.unwrap();
+        tokio_fs_ext::write(dev_dir.join("index.js"), "export default 1")
+            .await
+            .unwrap();
+
+        project.link_package("@scope/ui", &dev_dir).await.unwrap();
+        assert_eq!(
+            project
+                .read_to_string("node_modules/@scope/ui/index.js")
+                .await
+                .unwrap(),
+            "export default 1"
+        );
+        project.unlink_fuse("node_modules/@scope/ui").await.unwrap();
+        assert!(
+            !project
+                .exists("node_modules/@scope/ui/index.js")
+                .await
+                .unwrap()
+        );
+
+        for name in ["", "..", "../escape", "/abs", "a/b", "@scope/../x"] {
+            let err = project.link_package(name, &dev_dir).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{name:?}");
+        }
+        assert!(tokio_fs_ext::metadata(base.join("escape")).await.is_err());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_read_install_log() {
+        let base = Path::new("/test_project_install_log");
+        let project = project_at(base).await;
+        assert!(project.read_install_log().await.unwrap().is_empty());
+
+        let log_path = base.join(install_log::INSTALL_LOG_FILE);
+        for timestamp_ms in [1, 2, 3] {
+            let entry = InstallLogEntry {
+                timestamp_ms,
+                cached: vec![format!("pkg@{timestamp_ms}.0.0")],
+                ..Default::default()
+            };
+            install_log::append(&log_path, &entry).await.unwrap();
+        }
+        let entries = project.read_install_log().await.unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.timestamp_ms).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(entries[1].cached, ["pkg@2.0.0"]);
+
+        // A truncated line makes the log unreadable rather than silently
+        // dropping entries
+        let mut content = tokio_fs_ext::read(&log_path).await.unwrap();
+        content.extend_from_slice(b"{\"timestamp_ms\": 4, \"dur");
+        tokio_fs_ext::write(&log_path, &content).await.unwrap();
+        assert!(project.read_install_log().await.is_err());
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_walk_dir() {
+        let base = Path::new("/test_project_walk_dir");
+        let (project, dst) = linked_project(
+            base,
+            "pkg",
+            &[
+                ("package.json", ""),
+                ("lib/index.js", ""),
+                ("lib/types/index.d.ts", ""),
+            ],
+        )
+        .await;
+
+        assert_eq!(
+            project.walk_dir(&dst, usize::MAX).await.unwrap(),
+            [
+                dst.join("lib/index.js"),
+                dst.join("lib/types/index.d.ts"),
+                dst.join("package.json"),
+            ]
+        );
+        assert_eq!(
+            project.walk_dir(&dst, 1).await.unwrap(),
+            [dst.join("lib/index.js"), dst.join("package.json")]
+        );
+        assert_eq!(
+            project.walk_dir(&dst, 0).await.unwrap(),
+            [dst.join("package.json")]
+        );
+
+        // Directories too, typed through the fuse link
+        let entries: Vec<_> = project
+            .read_dir_recursive(&dst, 1)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(path, meta)| (path, meta.is_dir()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (dst.join("lib"), true),
+                (dst.join("lib/index.js"), false),
+                (dst.join("lib/types"), true),
+                (dst.join("package.json"), false),
+            ]
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_read_to_string() {
+        let base = Path::new("/test_project_read_to_string");
+        let (project, _) =
+            linked_project(base, "pkg", &[("index.js", "module.exports = 'é'")]).await;
+        tokio_fs_ext::write(base.join("stores/pkg-1.0.0/binary.bin"), [0xff, 0xfe])
+            .await
+            .unwrap();
+        tokio_fs_ext::write(base.join("plain.txt"), "plain")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            project
+                .read_to_string(base.join("node_modules/pkg/index.js"))
+                .await
+                .unwrap(),
+            "module.exports = 'é'"
+        );
+        assert_eq!(
+            project
+                .read_to_string(base.join("plain.txt"))
+                .await
+                .unwrap(),
+            "plain"
+        );
+        let err = project
+            .read_to_string(base.join("node_modules/pkg/binary.bin"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_get_package_json() {
+        let base = Path::new("/test_project_package_json");
+        let (project, install_path) = linked_project(
+            base,
+            "pkg",
+            &[("package.json", r#"{"name":"pkg","version":"1.0.0"}"#)],
+        )
+        .await;
+
+        let package_json = project.get_package_json(&install_path).await.unwrap();
+        assert_eq!(package_json["version"], "1.0.0");
+        assert_eq!(
+            project.get_package_json_str(&install_path).await.unwrap(),
+            r#"{"name":"pkg","version":"1.0.0"}"#
+        );
+
+        let err = project
+            .get_package_json(base.join("node_modules/missing"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(err.to_string().contains("node_modules/missing"));
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_generate_import_map() {
+        let base = Path::new("/test_project_import_map");
+        let (project, _) = linked_project(
+            base,
+            "@scope/esm",
+            &[(
+                "package.json",
+                r#"{"main":"index.cjs","module":"./dist/index.mjs"}"#,
+            )],
+        )
+        .await;
+        tokio_fs_ext::create_dir_all(base.join("node_modules/plain"))
+            .await
+            .unwrap();
+        project
+            .write(base.join("node_modules/plain/package.json"), "{}")
+            .await
+            .unwrap();
+
+        let lock = PackageLock::from_json(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {},
+                    "node_modules/@scope/esm": { "version": "1.0.0" },
+                    "node_modules/plain": { "version": "1.0.0" },
+                    "node_modules/plain/node_modules/nested": { "version": "1.0.0" },
+                    "node_modules/missing": { "version": "1.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let import_map = project
+            .generate_import_map(&lock, "https://example.com/app/")
+            .await
+            .unwrap();
+        let import_map: serde_json::Value = serde_json::from_str(&import_map).unwrap();
+        assert_eq!(
+            import_map,
+            serde_json::json!({
+                "imports": {
+                    "@scope/esm": "https://example.com/app/node_modules/@scope/esm/dist/index.mjs",
+                    "@scope/esm/": "https://example.com/app/node_modules/@scope/esm/",
+                    "plain": "https://example.com/app/node_modules/plain/index.js",
+                    "plain/": "https://example.com/app/node_modules/plain/",
+                }
+            })
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(base).await;
+    }
+}
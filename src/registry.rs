@@ -0,0 +1,483 @@
+//! npm registry configuration — default and per-scope registries, as
+//! configured in `.npmrc`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// The public npm registry.
+pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// Registry URL prefixes treated as "the default registry" when rewriting
+/// lockfile URLs for scoped registries.
+const DEFAULT_REGISTRY_ALIASES: [&str; 2] = [DEFAULT_REGISTRY, "https://registry.yarnpkg.com"];
+
+/// npm registry tarball URL: `<registry>/<name>/-/<basename>-<version>.tgz`.
+pub fn registry_tarball_url(registry: &str, name: &str, version: &str) -> String {
+    let registry = registry.trim_end_matches('/');
+    let basename = name.rsplit('/').next().unwrap_or(name);
+    format!("{registry}/{name}/-/{basename}-{version}.tgz")
+}
+
+/// Whether a lockfile `resolved` value is a git source, e.g.
+/// `git+https://github.com/org/repo.git#<commit>`.
+pub fn is_git_url(resolved: &str) -> bool {
+    resolved.starts_with("git+") || resolved.starts_with("git://")
+}
+
+/// Tarball URL of a git source pinned to a commit:
+/// `git+ssh://git@github.com/org/repo.git#<sha>` →
+/// `https://github.com/org/repo/archive/<sha>.tar.gz`.
+///
+/// GitHub, GitLab and Bitbucket are recognised. `None` for other hosts and
+/// for sources without a commit hash (e.g. `#main`), which would need a
+/// git client to resolve.
+pub fn git_tarball_url(resolved: &str) -> Option<String> {
+    let (repo, commit) = resolved.split_once('#')?;
+    if commit.is_empty() || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    // `host/org/repo`, also for scp-style `git@host:org/repo`
+    let rest = repo.split_once("//")?.1;
+    let rest = rest.split_once('@').map_or(rest, |(_, rest)| rest);
+    let (host, path) = rest.split_once(['/', ':'])?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    let repo_name = path.rsplit('/').next().filter(|name| !name.is_empty())?;
+    Some(match host {
+        "github.com" => format!("https://github.com/{path}/archive/{commit}.tar.gz"),
+        "gitlab.com" => {
+            format!("https://gitlab.com/{path}/-/archive/{commit}/{repo_name}-{commit}.tar.gz")
+        }
+        "bitbucket.org" => format!("https://bitbucket.org/{path}/get/{commit}.tar.gz"),
+        _ => return None,
+    })
+}
+
+/// Which registries packages are fetched from, and how to authenticate.
+///
+/// `Debug` lists the hosts that have auth tokens but never the tokens.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct RegistryConfig {
+    /// Default registry (`registry=`), when not the public npm registry
+    pub registry: Option<String>,
+    /// Registry per scope, e.g. `@company` → `https://npm.example.com`
+    pub scope_registries: HashMap<String, String>,
+    /// Bearer token per registry host, e.g. `npm.example.com` → token
+    pub auth_tokens: HashMap<String, String>,
+    /// Proxy that downloads are routed through, for registries that send
+    /// no CORS headers; see [`proxied_url`](Self::proxied_url)
+    pub cors_proxy: Option<String>,
+    /// Forward registry auth tokens through the CORS proxy (default: no,
+    /// the proxy would see them)
+    pub cors_proxy_auth: bool,
+}
+
+impl fmt::Debug for RegistryConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hosts: Vec<&String> = self.auth_tokens.keys().collect();
+        hosts.sort();
+        f.debug_struct("RegistryConfig")
+            .field("registry", &self.registry)
+            .field("scope_registries", &self.scope_registries)
+            .field("auth_tokens", &hosts)
+            .field("cors_proxy", &self.cors_proxy)
+            .field("cors_proxy_auth", &self.cors_proxy_auth)
+            .finish()
+    }
+}
+
+impl RegistryConfig {
+    /// Parse the registry settings of an `.npmrc` file.
+    ///
+    /// Understands `registry=<url>`, `@scope:registry=<url>` and
+    /// `//<host>/:_authToken=<token>`; other keys are ignored, and lines
+    /// that are not `key=value` are skipped with a warning, as npm does.
+    pub fn from_npmrc(content: &str) -> Self {
+        let mut config = Self::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                tracing::warn!(".npmrc:{}: expected key=value, skipped", index + 1);
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+            let registry_url = || value.trim_end_matches('/').to_string();
+
+            if key == "registry" {
+                config.registry = Some(registry_url());
+            } else if let Some(registry) = key.strip_suffix(":_authToken")
+                && let Some(host) = url_host(registry)
+            {
+                config
+                    .auth_tokens
+                    .insert(host.to_string(), value.to_string());
+            } else if let Some(scope) = key.strip_suffix(":registry")
+                && scope.starts_with('@')
+            {
+                config
+                    .scope_registries
+                    .insert(scope.to_string(), registry_url());
+            }
+        }
+        config
+    }
+
+    /// Use `url` as the default registry.
+    pub fn with_registry(mut self, url: &str) -> Self {
+        self.registry = Some(url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Fetch packages of `scope` (e.g. `@company`) from `url`.
+    pub fn with_scope_registry(mut self, scope: &str, url: &str) -> Self {
+        self.scope_registries
+            .insert(scope.to_string(), url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Send `token` to `host`; see [`set_auth_token`](Self::set_auth_token).
+    pub fn with_auth_token(mut self, host: &str, token: &str) -> Self {
+        self.set_auth_token(host, token);
+        self
+    }
+
+    /// Route downloads through `proxy`; see
+    /// [`set_cors_proxy`](Self::set_cors_proxy).
+    pub fn with_cors_proxy(mut self, proxy: &str) -> Self {
+        self.set_cors_proxy(proxy);
+        self
+    }
+
+    /// Send auth tokens through the CORS proxy; see
+    /// [`set_cors_proxy_auth`](Self::set_cors_proxy_auth).
+    pub fn with_cors_proxy_auth(mut self, forward: bool) -> Self {
+        self.set_cors_proxy_auth(forward);
+        self
+    }
+
+    /// Overlay `other` on top of `self`; settings in `other` win.
+    pub fn merge(&mut self, other: RegistryConfig) {
+        if other.registry.is_some() {
+            self.registry = other.registry;
+        }
+        if other.cors_proxy.is_some() {
+            self.cors_proxy = other.cors_proxy;
+            self.cors_proxy_auth = other.cors_proxy_auth;
+        }
+        self.scope_registries.extend(other.scope_registries);
+        self.auth_tokens.extend(other.auth_tokens);
+    }
+
+    /// Set the bearer token sent to `host` (e.g. `npm.example.com`).
+    pub fn set_auth_token(&mut self, host: &str, token: &str) {
+        self.auth_tokens.insert(host.to_string(), token.to_string());
+    }
+
+    /// The bearer token for the host of `url`, if any.
+    pub fn auth_token_for(&self, url: &str) -> Option<&str> {
+        let host = url_host(url)?;
+        self.auth_tokens.get(host).map(String::as_str)
+    }
+
+    /// Route downloads through `proxy`: either a URL template containing
+    /// `{url}` (e.g. `https://proxy.example.com/fetch?u={url}`) or a prefix
+    /// the target URL is appended to (e.g. `https://proxy.example.com/`).
+    pub fn set_cors_proxy(&mut self, proxy: &str) {
+        self.cors_proxy = Some(proxy.to_string());
+    }
+
+    /// Download directly from the registry again.
+    pub fn clear_cors_proxy(&mut self) {
+        self.cors_proxy = None;
+    }
+
+    /// Whether requests through the CORS proxy carry the registry's auth
+    /// token. Off by default: only enable it for a proxy you trust with
+    /// your tokens.
+    pub fn set_cors_proxy_auth(&mut self, forward: bool) {
+        self.cors_proxy_auth = forward;
+    }
+
+    /// The bearer token to send with a request for `url`: the registry
+    /// host's, unless the request goes through a CORS proxy that is not
+    /// trusted with it (see [`set_cors_proxy_auth`](Self::set_cors_proxy_auth)).
+    pub fn request_token_for(&self, url: &str) -> Option<&str> {
+        if self.cors_proxy.is_some() && !self.cors_proxy_auth {
+            return None;
+        }
+        self.auth_token_for(url)
+    }
+
+    /// `url` as requested through the CORS proxy, if one is set.
+    ///
+    /// In a template, `{url}` inside the query (after `?`) is replaced by
+    /// the percent-encoded URL, elsewhere by the URL verbatim.
+    pub fn proxied_url(&self, url: &str) -> String {
+        let Some(proxy) = self.cors_proxy.as_deref() else {
+            return url.to_string();
+        };
+        match proxy.find("{url}") {
+            Some(at) if proxy[..at].contains('?') => {
+                proxy.replace("{url}", &String::from(js_sys::encode_uri_component(url)))
+            }
+            Some(_) => proxy.replace("{url}", url),
+            None => format!("{proxy}{url}"),
+        }
+    }
+
+    /// The registry serving `name`: its scope registry, else the default.
+    pub fn registry_for(&self, name: &str) -> &str {
+        self.scope_registry(name)
+            .or(self.registry.as_deref())
+            .unwrap_or(DEFAULT_REGISTRY)
+    }
+
+    /// Tarball URL to download `name@version` from, given its lockfile
+    /// `resolved` field.
+    ///
+    /// Packages of a scope with its own registry are fetched from that
+    /// registry when `resolved` is missing or points at the default
+    /// registry; other packages without `resolved` from the default
+    /// registry, when one is set (`registry=`). Git sources on a known
+    /// host become that host's tarball URL (see [`git_tarball_url`]).
+    /// Everything else keeps `resolved` as-is; `None` means there is
+    /// nothing to download.
+    pub fn tarball_url(&self, name: &str, version: &str, resolved: Option<&str>) -> Option<String> {
+        if let Some(git) = resolved.filter(|r| is_git_url(r)) {
+            return Some(git_tarball_url(git).unwrap_or_else(|| git.to_string()));
+        }
+        let Some(scope_registry) = self.scope_registry(name) else {
+            return match resolved {
+                Some(resolved) => Some(resolved.to_string()),
+                None => self
+                    .registry
+                    .as_deref()
+                    .map(|registry| registry_tarball_url(registry, name, version)),
+            };
+        };
+        let Some(resolved) = resolved else {
+            return Some(registry_tarball_url(scope_registry, name, version));
+        };
+        let rewritten = DEFAULT_REGISTRY_ALIASES.iter().find_map(|default| {
+            let path = resolved.strip_prefix(default)?;
+            path.starts_with('/')
+                .then(|| format!("{scope_registry}{path}"))
+        });
+        Some(rewritten.unwrap_or_else(|| resolved.to_string()))
+    }
+
+    fn scope_registry(&self, name: &str) -> Option<&str> {
+        let (scope, _) = name.split_once('/')?;
+        self.scope_registries.get(scope).map(String::as_str)
+    }
+}
+
+/// Host (with port) of an absolute or protocol-relative URL:
+/// `https://npm.example.com/x` and `//npm.example.com/` → `npm.example.com`.
+fn url_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("//")?.1;
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const NPMRC: &str = r#"
+# company packages
+@company:registry=https://npm.example.com/
+registry = https://mirror.example.com
+//npm.example.com/:_authToken=secret-token
+save-exact=true
+"#;
+
+    #[wasm_bindgen_test]
+    fn test_from_npmrc() {
+        let config = RegistryConfig::from_npmrc(NPMRC);
+        assert_eq!(
+            config.registry.as_deref(),
+            Some("https://mirror.example.com")
+        );
+        assert_eq!(
+            config.scope_registries.get("@company").map(String::as_str),
+            Some("https://npm.example.com")
+        );
+        assert_eq!(
+            config.registry_for("@company/ui"),
+            "https://npm.example.com"
+        );
+        assert_eq!(config.registry_for("lodash"), "https://mirror.example.com");
+
+        // Malformed lines are skipped, not fatal
+        let config = RegistryConfig::from_npmrc("not a setting\nregistry=https://r.example.com/");
+        assert_eq!(config.registry.as_deref(), Some("https://r.example.com"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_builder_matches_npmrc() {
+        let built = RegistryConfig::default()
+            .with_scope_registry("@company", "https://npm.example.com/")
+            .with_registry("https://mirror.example.com")
+            .with_auth_token("npm.example.com", "secret-token");
+        assert_eq!(built, RegistryConfig::from_npmrc(NPMRC));
+
+        let proxied = built.with_cors_proxy("https://proxy.example.com/");
+        assert_eq!(
+            proxied.cors_proxy.as_deref(),
+            Some("https://proxy.example.com/")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_auth_tokens() {
+        let mut config = RegistryConfig::from_npmrc(NPMRC);
+        assert_eq!(
+            config.auth_token_for("https://npm.example.com/@company/ui/-/ui-1.0.0.tgz"),
+            Some("secret-token")
+        );
+        assert_eq!(config.auth_token_for("https://registry.npmjs.org/x"), None);
+
+        config.set_auth_token("registry.npmjs.org", "other-token");
+        assert_eq!(
+            config.auth_token_for("https://registry.npmjs.org/x"),
+            Some("other-token")
+        );
+
+        // Not sent through a CORS proxy unless allowed
+        let url = "https://registry.npmjs.org/x";
+        assert_eq!(config.request_token_for(url), Some("other-token"));
+        config.set_cors_proxy("https://proxy.example.com/");
+        assert_eq!(config.request_token_for(url), None);
+        config.set_cors_proxy_auth(true);
+        assert_eq!(config.request_token_for(url), Some("other-token"));
+
+        // Tokens never show up in Debug output
+        let debug = format!("{config:?}");
+        assert!(debug.contains("npm.example.com"));
+        assert!(!debug.contains("secret-token"));
+        assert!(!debug.contains("other-token"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_tarball_url() {
+        let config = RegistryConfig::from_npmrc(NPMRC);
+
+        // Missing `resolved`: built from the scope registry
+        assert_eq!(
+            config.tarball_url("@company/ui", "1.0.0", None).as_deref(),
+            Some("https://npm.example.com/@company/ui/-/ui-1.0.0.tgz")
+        );
+        // Default registry URL: rewritten to the scope registry
+        assert_eq!(
+            config
+                .tarball_url(
+                    "@company/ui",
+                    "1.0.0",
+                    Some("https://registry.npmjs.org/@company/ui/-/ui-1.0.0.tgz")
+                )
+                .as_deref(),
+            Some("https://npm.example.com/@company/ui/-/ui-1.0.0.tgz")
+        );
+        // Other URLs and resolved unscoped packages are left alone
+        let other = "https://cdn.example.com/ui-1.0.0.tgz";
+        assert_eq!(
+            config
+                .tarball_url("@company/ui", "1.0.0", Some(other))
+                .as_deref(),
+            Some(other)
+        );
+        assert_eq!(
+            config
+                .tarball_url("lodash", "4.17.21", Some(other))
+                .as_deref(),
+            Some(other)
+        );
+        // Missing `resolved`: built from the default registry, if set
+        assert_eq!(
+            config.tarball_url("lodash", "4.17.21", None).as_deref(),
+            Some("https://mirror.example.com/lodash/-/lodash-4.17.21.tgz")
+        );
+        assert_eq!(
+            RegistryConfig::default().tarball_url("lodash", "4.17.21", None),
+            None
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_git_tarball_url() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        for source in [
+            format!("git+https://github.com/org/repo.git#{sha}"),
+            format!("git+ssh://git@github.com/org/repo.git#{sha}"),
+            format!("git+ssh://git@github.com:org/repo.git#{sha}"),
+            format!("git://github.com/org/repo#{sha}"),
+        ] {
+            assert!(is_git_url(&source));
+            assert_eq!(
+                git_tarball_url(&source),
+                Some(format!("https://github.com/org/repo/archive/{sha}.tar.gz")),
+                "{source}"
+            );
+        }
+        assert_eq!(
+            git_tarball_url(&format!("git+https://gitlab.com/group/sub/repo.git#{sha}")),
+            Some(format!(
+                "https://gitlab.com/group/sub/repo/-/archive/{sha}/repo-{sha}.tar.gz"
+            ))
+        );
+        assert_eq!(
+            git_tarball_url(&format!("git+ssh://git@bitbucket.org/org/repo.git#{sha}")),
+            Some(format!("https://bitbucket.org/org/repo/get/{sha}.tar.gz"))
+        );
+
+        // Unknown hosts and unpinned refs are not rewritten
+        let unknown = format!("git+https://git.example.com/org/repo.git#{sha}");
+        assert_eq!(git_tarball_url(&unknown), None);
+        assert_eq!(
+            git_tarball_url("git+https://github.com/org/repo.git#main"),
+            None
+        );
+        assert_eq!(
+            RegistryConfig::default()
+                .tarball_url("repo", "1.0.0", Some(&unknown))
+                .as_deref(),
+            Some(unknown.as_str())
+        );
+        assert!(!is_git_url("https://registry.npmjs.org/a/-/a-1.0.0.tgz"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_proxied_url() {
+        let url = "https://npm.example.com/ui/-/ui-1.0.0.tgz";
+        let mut config = RegistryConfig::default();
+        assert_eq!(config.proxied_url(url), url);
+
+        config.set_cors_proxy("https://proxy.example.com/");
+        assert_eq!(
+            config.proxied_url(url),
+            "https://proxy.example.com/https://npm.example.com/ui/-/ui-1.0.0.tgz"
+        );
+
+        config.set_cors_proxy("https://proxy.example.com/fetch?u={url}&cache=1");
+        assert_eq!(
+            config.proxied_url(url),
+            "https://proxy.example.com/fetch?u=https%3A%2F%2Fnpm.example.com%2Fui%2F-%2Fui-1.0.0.tgz&cache=1"
+        );
+
+        // Outside the query the URL is a path suffix, kept verbatim
+        config.set_cors_proxy("https://proxy.example.com/raw/{url}");
+        assert_eq!(
+            config.proxied_url(url),
+            "https://proxy.example.com/raw/https://npm.example.com/ui/-/ui-1.0.0.tgz"
+        );
+
+        config.clear_cors_proxy();
+        assert_eq!(config.proxied_url(url), url);
+    }
+}
@@ -1,18 +1,111 @@
 //! Tgz store — download, verify integrity, and persist to OPFS.
 
+// Registry settings are read out of their lock before any request is
+// made; no guard may be held across an `.await`.
+#![deny(clippy::await_holding_lock)]
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use bytes::Bytes;
+use futures::{AsyncWriteExt, StreamExt};
+use wasmtimer::std::{SystemTime, UNIX_EPOCH};
 
-use crate::archive;
+use crate::archive::{self, IntegrityHasher};
 use crate::config::Config;
 use crate::error::{OpfsError, VerifyResult};
+use crate::package_lock::is_valid_package_name;
+use crate::registry::{RegistryConfig, is_git_url};
+
+/// Where a package lives in the store and in `node_modules`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageStorePaths {
+    /// Downloaded tarball, e.g. `/stores/lodash/-/lodash-4.17.21.tgz`
+    pub tgz_path: PathBuf,
+    /// Extraction directory (the tgz path with `.tgz` stripped)
+    pub extract_dir: PathBuf,
+    /// Sentinel written once extraction has completed
+    pub resolved_marker: PathBuf,
+    /// The `fuse.link` file under the install path
+    pub fuse_link: PathBuf,
+}
+
+/// A package extracted into the store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledPackage {
+    pub name: String,
+    /// Version from the extracted `package.json`
+    pub version: String,
+    /// Extraction directory, e.g. `/stores/lodash/-/lodash-4.17.21`
+    pub store_path: PathBuf,
+    /// Total size of the extracted files
+    pub size_bytes: u64,
+}
+
+/// Options for [`Store::prune`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneOptions {
+    /// Report what would be removed without deleting anything
+    pub dry_run: bool,
+}
+
+/// Outcome of [`Store::prune`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Store-relative tgz paths of the removed packages, e.g.
+    /// `lodash/-/lodash-4.17.21.tgz`, sorted; the extraction directory's
+    /// path when its tarball was already gone
+    pub removed_packages: Vec<String>,
+    /// Bytes of tarballs and extracted files removed
+    pub freed_bytes: u64,
+}
+
+/// Upper bound on a server-provided `Retry-After` delay.
+const MAX_RETRY_AFTER_SECS: u64 = 60;
+
+/// Upper bound on the exponential back-off delay between retries.
+const MAX_RETRY_DELAY_MS: u64 = 30_000;
+
+/// Sentinel file marking `extract_dir` as completely extracted.
+pub fn resolved_marker_path(extract_dir: &Path) -> PathBuf {
+    PathBuf::from(format!("{}._resolved", extract_dir.display()))
+}
+
+/// Write `contents` to `path` without ever leaving it half-written: the
+/// data goes to `<path>.tmp.<timestamp>.<n>` first and is renamed over
+/// `path` once complete. The temp file is removed on failure.
+pub async fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    static TEMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let seq = TEMP_SEQ.fetch_add(1, Ordering::Relaxed);
+    let temp = PathBuf::from(format!("{}.tmp.{timestamp}.{seq}", path.display()));
+
+    let result = match tokio_fs_ext::write(&temp, contents).await {
+        Ok(()) => tokio_fs_ext::rename(&temp, path).await,
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = tokio_fs_ext::remove_file(&temp).await;
+    }
+    result
+}
 
 /// Manages the tgz file store on OPFS.
 pub struct Store {
     root: PathBuf,
     retries: u32,
     retry_base_delay_ms: u64,
+    stream_threshold: u64,
+    registry: RwLock<RegistryConfig>,
+    /// `Content-Length` per tarball URL, see [`download_size`](Self::download_size)
+    download_sizes: RwLock<HashMap<String, u64>>,
+    client: reqwest::Client,
 }
 
 impl Store {
@@ -21,15 +114,76 @@ impl Store {
             root: config.store_root.clone(),
             retries: config.download_retries,
             retry_base_delay_ms: config.retry_base_delay_ms,
+            stream_threshold: config.stream_download_threshold,
+            registry: RwLock::new(config.registry.clone()),
+            download_sizes: RwLock::new(HashMap::new()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Snapshot of the registry configuration.
+    pub fn registry_config(&self) -> RegistryConfig {
+        self.registry
+            .read()
+            .map(|registry| registry.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace the registry configuration.
+    pub fn set_registry_config(&self, registry: RegistryConfig) {
+        if let Ok(mut current) = self.registry.write() {
+            *current = registry;
         }
     }
 
     /// Compute the OPFS path where a package tgz is stored.
+    ///
+    /// The file name is the last path segment of `tgz_url`, without any
+    /// query string or fragment (e.g. `lodash-4.17.21.tgz?cache=1`).
     pub fn tgz_path(&self, name: &str, tgz_url: &str) -> PathBuf {
-        let file_name = tgz_url.rsplit('/').next().unwrap_or("package.tgz");
+        let file_name = match reqwest::Url::parse(tgz_url) {
+            Ok(url) => url
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .map(str::to_string),
+            // Not an absolute URL, e.g. a bare path
+            Err(_) => tgz_url
+                .split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit('/').next())
+                .map(str::to_string),
+        };
+        let file_name = file_name
+            .filter(|f| !f.is_empty())
+            .unwrap_or_else(|| "package.tgz".to_string());
         self.root.join(name).join("-").join(file_name)
     }
 
+    /// Store directory a local (`file:` or workspace) package is copied to
+    /// on install, `<name>/-/local-<hash>`, keyed by its absolute
+    /// `source_dir` so that two sources of the same name never share a
+    /// copy. It is marked complete like an extraction directory.
+    pub fn local_package_dir(&self, name: &str, source_dir: &Path) -> PathBuf {
+        let hash = archive::sig_sha256(source_dir.to_string_lossy().as_bytes());
+        self.root
+            .join(name)
+            .join("-")
+            .join(format!("local-{}", &hash[..16]))
+    }
+
+    /// Compute every store path for a package installed at `path_key`
+    /// (e.g. `node_modules/lodash`).
+    pub fn package_paths(&self, name: &str, tgz_url: &str, path_key: &Path) -> PackageStorePaths {
+        let tgz_path = self.tgz_path(name, tgz_url);
+        let extract_dir = tgz_path.with_extension("");
+        PackageStorePaths {
+            resolved_marker: resolved_marker_path(&extract_dir),
+            tgz_path,
+            extract_dir,
+            fuse_link: path_key.join("fuse.link"),
+        }
+    }
+
     /// Check whether the tgz for a package is already on disk.
     pub async fn is_cached(&self, name: &str, tgz_url: &str) -> bool {
         let path = self.tgz_path(name, tgz_url);
@@ -39,6 +193,124 @@ impl Store {
             .unwrap_or(false)
     }
 
+    /// Every completely extracted package in the store, sorted by name and
+    /// version.
+    ///
+    /// Scans `<root>/<name>/-/` (and `<root>/@scope/<name>/-/`) for
+    /// extraction directories with a resolved marker; the version comes
+    /// from their `package.json`. An empty or missing store yields an
+    /// empty list.
+    pub async fn list_packages(&self) -> Result<Vec<InstalledPackage>, OpfsError> {
+        let mut packages = Vec::new();
+        for (name, dir) in self.package_dirs().await? {
+            let versions_dir = dir.join("-");
+            for store_path in subdirs(&versions_dir).await? {
+                if tokio_fs_ext::metadata(resolved_marker_path(&store_path))
+                    .await
+                    .is_err()
+                {
+                    continue;
+                }
+                let Some(version) = read_version(&store_path).await else {
+                    tracing::warn!("no version in {}/package.json", store_path.display());
+                    continue;
+                };
+                packages.push(InstalledPackage {
+                    name: name.clone(),
+                    version,
+                    size_bytes: dir_size(&store_path).await?,
+                    store_path,
+                });
+            }
+        }
+        packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        Ok(packages)
+    }
+
+    /// Remove every package whose tgz path (see [`tgz_path`](Self::tgz_path))
+    /// is not in `keep`: the tarball, its extraction directory and resolved
+    /// marker, and any partial download. Any other entry under a package's
+    /// `-/` directory is removed too.
+    ///
+    /// With [`PruneOptions::dry_run`] nothing is deleted, but the report is
+    /// the same.
+    pub async fn prune(
+        &self,
+        keep: &HashSet<PathBuf>,
+        opts: PruneOptions,
+    ) -> Result<PruneReport, OpfsError> {
+        let mut removed = BTreeSet::new();
+        let mut freed_bytes = 0;
+
+        // Entries a kept tarball owns, named after its actual file name
+        // (`.tgz`, `.tar.gz`, ...), never rebuilt from a directory name
+        let owned: HashSet<PathBuf> = keep
+            .iter()
+            .flat_map(|tgz_path| {
+                let extract_dir = tgz_path.with_extension("");
+                [
+                    tgz_path.clone(),
+                    PathBuf::from(format!("{}.part", tgz_path.display())),
+                    resolved_marker_path(&extract_dir),
+                    extract_dir,
+                ]
+            })
+            .collect();
+
+        for (_, dir) in self.package_dirs().await? {
+            let versions_dir = dir.join("-");
+            let entries = match tokio_fs_ext::read_dir(&versions_dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let mut kept_any = false;
+            // Extraction directory name -> name to report
+            let mut removed_here = BTreeMap::new();
+            for entry in entries {
+                let file_name = entry?.file_name().to_string_lossy().into_owned();
+                let path = versions_dir.join(&file_name);
+                if owned.contains(&path) {
+                    kept_any = true;
+                    continue;
+                }
+                let metadata = tokio_fs_ext::metadata(&path).await?;
+
+                if metadata.is_dir() {
+                    freed_bytes += dir_size(&path).await?;
+                    if !opts.dry_run {
+                        tokio_fs_ext::remove_dir_all(&path).await?;
+                    }
+                } else {
+                    freed_bytes += metadata.len();
+                    if !opts.dry_run {
+                        tokio_fs_ext::remove_file(&path).await?;
+                    }
+                }
+                let (extract_dir, tgz) = entry_owner(&file_name, metadata.is_dir());
+                let reported = removed_here
+                    .entry(extract_dir)
+                    .or_insert_with_key(|dir| dir.clone());
+                if let Some(tgz) = tgz {
+                    *reported = tgz;
+                }
+            }
+            for name in removed_here.into_values() {
+                if let Ok(relative) = versions_dir.join(name).strip_prefix(&self.root) {
+                    removed.insert(relative.to_string_lossy().into_owned());
+                }
+            }
+            if !kept_any && !opts.dry_run {
+                tokio_fs_ext::remove_dir_all(&dir).await?;
+            }
+        }
+
+        Ok(PruneReport {
+            removed_packages: removed.into_iter().collect(),
+            freed_bytes,
+        })
+    }
+
     /// Ensure a tgz is on disk — download if missing.
     ///
     /// Unlike [`fetch_tgz`], this does **not** read or re-verify cached files.
@@ -56,6 +328,22 @@ impl Store {
         tgz_url: &str,
         integrity: Option<&str>,
         shasum: Option<&str>,
+    ) -> Result<bool, OpfsError> {
+        self.ensure_tgz_with_retries(name, version, tgz_url, integrity, shasum, self.retries)
+            .await
+    }
+
+    /// [`ensure_tgz`] with an explicit number of download attempts instead
+    /// of [`Config::download_retries`].
+    #[tracing::instrument(skip_all, fields(package = %name, version = %version, url = %tgz_url))]
+    pub async fn ensure_tgz_with_retries(
+        &self,
+        name: &str,
+        version: &str,
+        tgz_url: &str,
+        integrity: Option<&str>,
+        shasum: Option<&str>,
+        retries: u32,
     ) -> Result<bool, OpfsError> {
         let store_path = self.tgz_path(name, tgz_url);
 
@@ -70,16 +358,17 @@ impl Store {
         }
 
         // Not cached — download, verify, and persist.
-        let bytes = self.download_with_retry(tgz_url).await?;
-
-        if archive::verify_integrity(&bytes, integrity, shasum).is_failed() {
+        let verified = self
+            .with_retry(tgz_url, retries, || {
+                self.download_to_file(tgz_url, &store_path, integrity, shasum)
+            })
+            .await?;
+        if verified.is_failed() {
             return Err(OpfsError::IntegrityFailed {
                 package: name.to_string(),
                 version: version.to_string(),
             });
         }
-
-        self.save(&store_path, &bytes).await?;
         Ok(true)
     }
 
@@ -90,6 +379,7 @@ impl Store {
     ///
     /// Returns `(bytes, was_fresh)` where `was_fresh` is `true` when the tgz
     /// was re-downloaded (e.g. because the cached copy failed integrity).
+    #[tracing::instrument(skip_all, fields(package = %name, version = %version, url = %tgz_url))]
     pub async fn fetch_tgz(
         &self,
         name: &str,
@@ -113,7 +403,7 @@ impl Store {
         }
 
         // Download with retry
-        let bytes = self.download_with_retry(tgz_url).await?;
+        let bytes = self.download_with_retry(tgz_url, self.retries).await?;
 
         // Verify downloaded bytes
         if archive::verify_integrity(&bytes, integrity, shasum).is_failed() {
@@ -128,31 +418,173 @@ impl Store {
         Ok((Bytes::from(bytes), true))
     }
 
+    /// The store directory of package `name`, holding all its versions.
+    pub fn package_dir(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    /// Remove every stored version of package `name` (tarballs and
+    /// extraction directories), e.g. to force a buggy cached copy to be
+    /// downloaded again. Returns the number of bytes freed; 0 when nothing
+    /// was stored.
+    ///
+    /// Fails for anything but `name` or `@scope/name` (see
+    /// [`is_valid_package_name`]), so `name` cannot reach outside the store.
+    pub async fn remove_package(&self, name: &str) -> Result<u64, OpfsError> {
+        if !is_valid_package_name(name) {
+            return Err(OpfsError::Other(format!("invalid package name: {name:?}")));
+        }
+        let dir = self.package_dir(name);
+        let freed_bytes = match dir_size(&dir).await {
+            Ok(size) => size,
+            Err(OpfsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        tokio_fs_ext::remove_dir_all(&dir).await?;
+        Ok(freed_bytes)
+    }
+
+    /// Size of the tarball at `tgz_url` in bytes, from the
+    /// `Content-Length` of a `HEAD` request. Sizes are remembered per URL.
+    ///
+    /// Returns `Ok(None)` when the response carries no length.
+    pub async fn download_size(&self, tgz_url: &str) -> Result<Option<u64>, OpfsError> {
+        if let Some(size) = self
+            .download_sizes
+            .read()
+            .ok()
+            .and_then(|sizes| sizes.get(tgz_url).copied())
+        {
+            return Ok(Some(size));
+        }
+
+        let resp = self.send(reqwest::Method::HEAD, tgz_url).await?;
+        let size = resp
+            .headers()
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok());
+        if let (Some(size), Ok(mut sizes)) = (size, self.download_sizes.write()) {
+            sizes.insert(tgz_url.to_string(), size);
+        }
+        Ok(size)
+    }
+
+    /// Check that the cached copy of a package is complete and intact: its
+    /// tgz is on disk and does not fail `integrity`/`shasum`, and it was
+    /// fully extracted (resolved marker present, directory non-empty).
+    ///
+    /// Returns `Ok(false)` for anything missing or corrupt, e.g. after the
+    /// page was closed mid-install, so a startup check can clear the
+    /// package before installing again.
+    pub async fn verify_cached(
+        &self,
+        name: &str,
+        tgz_url: &str,
+        integrity: Option<&str>,
+        shasum: Option<&str>,
+    ) -> Result<bool, OpfsError> {
+        let tgz_path = self.tgz_path(name, tgz_url);
+        let bytes = match tokio_fs_ext::read(&tgz_path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        if archive::verify_integrity(&bytes, integrity, shasum).is_failed() {
+            return Ok(false);
+        }
+
+        let extract_dir = tgz_path.with_extension("");
+        if tokio_fs_ext::metadata(resolved_marker_path(&extract_dir))
+            .await
+            .is_err()
+        {
+            return Ok(false);
+        }
+        Ok(match tokio_fs_ext::read_dir(&extract_dir).await {
+            Ok(mut entries) => entries.next().is_some(),
+            Err(_) => false,
+        })
+    }
+
     // ── private ──────────────────────────────────────────────────────
 
+    /// `(name, <root>/<name>)` for every package directory in the store.
+    async fn package_dirs(&self) -> Result<Vec<(String, PathBuf)>, OpfsError> {
+        let mut dirs = Vec::new();
+        for dir in subdirs(&self.root).await? {
+            let Some(dir_name) = dir.file_name().and_then(|n| n.to_str()).map(str::to_string)
+            else {
+                continue;
+            };
+            if dir_name.starts_with('@') {
+                for scoped in subdirs(&dir).await? {
+                    if let Some(name) = scoped.file_name().and_then(|n| n.to_str()) {
+                        dirs.push((format!("{dir_name}/{name}"), scoped));
+                    }
+                }
+            } else {
+                dirs.push((dir_name, dir));
+            }
+        }
+        Ok(dirs)
+    }
+
     async fn save(&self, path: &Path, bytes: &[u8]) -> Result<(), OpfsError> {
         if let Some(parent) = path.parent() {
             tokio_fs_ext::create_dir_all(parent).await?;
         }
-        tokio_fs_ext::write(path, bytes).await?;
+        write_atomic(path, bytes).await?;
         Ok(())
     }
 
-    async fn download_with_retry(&self, url: &str) -> Result<Vec<u8>, OpfsError> {
+    /// Download `url`, making up to `retries` attempts.
+    async fn download_with_retry(&self, url: &str, retries: u32) -> Result<Vec<u8>, OpfsError> {
+        self.with_retry(url, retries, || self.download_once(url))
+            .await
+    }
+
+    /// Run `attempt` against `url` up to `retries` times, and at least once.
+    ///
+    /// Only transient failures (see [`OpfsError::is_transient`]) are
+    /// retried, with exponential back-off capped at [`MAX_RETRY_DELAY_MS`]
+    /// or the registry's `Retry-After`.
+    async fn with_retry<T, F, Fut>(
+        &self,
+        url: &str,
+        retries: u32,
+        mut attempt_fn: F,
+    ) -> Result<T, OpfsError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, OpfsError>>,
+    {
+        let retries = retries.max(1);
         let mut last_err = None;
-        for attempt in 0..self.retries {
+        let mut retry_after: Option<u64> = None;
+        for attempt in 0..retries {
             if attempt > 0 {
-                let delay = self.retry_base_delay_ms.saturating_mul(1u64 << (attempt - 1).min(63));
+                // Honour the registry's Retry-After over our own back-off.
+                let delay = match retry_after.take() {
+                    Some(secs) => secs.min(MAX_RETRY_AFTER_SECS).saturating_mul(1000),
+                    None => self
+                        .retry_base_delay_ms
+                        .saturating_mul(1u64 << (attempt - 1).min(63))
+                        .min(MAX_RETRY_DELAY_MS),
+                };
                 wasmtimer::tokio::sleep(std::time::Duration::from_millis(delay)).await;
             }
-            match self.download_once(url).await {
-                Ok(b) => return Ok(b),
+            match attempt_fn().await {
+                Ok(value) => return Ok(value),
+                Err(e) if !e.is_transient() => return Err(e),
                 Err(e) => {
-                    tracing::warn!(
-                        "download {}/{} for {url} failed: {e}",
-                        attempt + 1,
-                        self.retries
-                    );
+                    tracing::warn!("download {}/{retries} for {url} failed: {e}", attempt + 1);
+                    if let OpfsError::RateLimited {
+                        retry_after: secs, ..
+                    } = &e
+                    {
+                        retry_after = *secs;
+                    }
                     last_err = Some(e);
                 }
             }
@@ -160,15 +592,469 @@ impl Store {
         Err(last_err.unwrap_or_else(|| OpfsError::Other(format!("download failed: {url}"))))
     }
 
+    /// Single download attempt into memory.
     async fn download_once(&self, url: &str) -> Result<Vec<u8>, OpfsError> {
-        let resp = reqwest::get(url).await?;
+        Ok(self.get(url).await?.bytes().await?.to_vec())
+    }
+
+    /// Single download attempt into `dest`, verified against
+    /// `integrity` / `shasum`. Nothing is left at `dest` unless the
+    /// download completed and did not fail verification.
+    ///
+    /// Responses above [`Config::stream_download_threshold`] are streamed
+    /// to disk chunk by chunk instead of being buffered in memory.
+    async fn download_to_file(
+        &self,
+        url: &str,
+        dest: &Path,
+        integrity: Option<&str>,
+        shasum: Option<&str>,
+    ) -> Result<VerifyResult, OpfsError> {
+        let resp = self.get(url).await?;
+        if resp
+            .content_length()
+            .is_none_or(|len| len <= self.stream_threshold)
+        {
+            let bytes = resp.bytes().await?;
+            let verified = archive::verify_integrity(&bytes, integrity, shasum);
+            if !verified.is_failed() {
+                self.save(dest, &bytes).await?;
+            }
+            return Ok(verified);
+        }
+
+        // Stream into a `.part` file and only move it into place once
+        // complete, so an interrupted download never looks cached.
+        let part = PathBuf::from(format!("{}.part", dest.display()));
+        let mut hasher = IntegrityHasher::new(integrity, shasum);
+        let streamed = self.download_streaming(resp, &part, &mut hasher).await;
+        let verified = hasher.finish();
+        match streamed {
+            Ok(written) if !verified.is_failed() => {
+                tracing::debug!("streamed {written} bytes from {url}");
+                tokio_fs_ext::rename(&part, dest).await?;
+                Ok(verified)
+            }
+            result => {
+                let _ = tokio_fs_ext::remove_file(&part).await;
+                result.map(|_| verified)
+            }
+        }
+    }
+
+    /// Write the body of `resp` to `dest` as it arrives, feeding every chunk
+    /// to `hasher`. Returns the number of bytes written.
+    async fn download_streaming(
+        &self,
+        resp: reqwest::Response,
+        dest: &Path,
+        hasher: &mut IntegrityHasher,
+    ) -> Result<u64, OpfsError> {
+        if let Some(parent) = dest.parent() {
+            tokio_fs_ext::create_dir_all(parent).await?;
+        }
+        let mut file = tokio_fs_ext::File::create(dest).await?;
+        let mut stream = resp.bytes_stream();
+        let mut written = 0u64;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        file.close().await?;
+        Ok(written)
+    }
+
+    /// Send a GET for `url`, authenticated with the registry's token when
+    /// one is configured for the URL's host, and check the response status.
+    ///
+    /// The request goes through the CORS proxy when one is configured; the
+    /// token, chosen by the registry host, then only goes along if
+    /// [`RegistryConfig::cors_proxy_auth`] allows it.
+    async fn get(&self, url: &str) -> Result<reqwest::Response, OpfsError> {
+        self.send(reqwest::Method::GET, url).await
+    }
+
+    /// Send a `method` request for `url`; see [`get`](Self::get).
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest::Response, OpfsError> {
+        // Left as-is by `RegistryConfig::tarball_url`: no tarball URL known
+        if is_git_url(url) {
+            return Err(OpfsError::Other(format!(
+                "cannot fetch git source {url}: only commits on GitHub, GitLab and \
+                 Bitbucket are supported"
+            )));
+        }
+        let (request_url, token) = match self.registry.read() {
+            Ok(registry) => (
+                registry.proxied_url(url),
+                registry.request_token_for(url).map(str::to_string),
+            ),
+            Err(_) => (url.to_string(), None),
+        };
+        let mut request = self.client.request(method, &request_url);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let resp = request.send().await?;
         let status = resp.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok());
+            return Err(OpfsError::RateLimited {
+                url: url.to_string(),
+                retry_after,
+            });
+        }
         if !status.is_success() {
             return Err(OpfsError::Http {
                 status: status.as_u16(),
                 url: url.to_string(),
             });
         }
-        Ok(resp.bytes().await?.to_vec())
+        Ok(resp)
+    }
+}
+
+/// Sub-directories of `dir`; empty when `dir` does not exist.
+async fn subdirs(dir: &Path) -> Result<Vec<PathBuf>, OpfsError> {
+    let entries = match tokio_fs_ext::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let path = dir.join(entry?.file_name());
+        if tokio_fs_ext::metadata(&path).await?.is_dir() {
+            dirs.push(path);
+        }
+    }
+    Ok(dirs)
+}
+
+/// Name of the extraction directory a store entry belongs to, and the
+/// tarball's file name when the entry is the tarball or its partial
+/// download.
+fn entry_owner(file_name: &str, is_dir: bool) -> (String, Option<String>) {
+    if is_dir {
+        return (file_name.to_string(), None);
+    }
+    if let Some(extract_dir) = file_name.strip_suffix("._resolved") {
+        return (extract_dir.to_string(), None);
+    }
+    let tgz = file_name.strip_suffix(".part").unwrap_or(file_name);
+    let extract_dir = Path::new(tgz).with_extension("");
+    (
+        extract_dir.to_string_lossy().into_owned(),
+        Some(tgz.to_string()),
+    )
+}
+
+/// `version` field of `<dir>/package.json`.
+async fn read_version(dir: &Path) -> Option<String> {
+    let content = tokio_fs_ext::read(dir.join("package.json")).await.ok()?;
+    let package_json: serde_json::Value = serde_json::from_slice(&content).ok()?;
+    package_json["version"].as_str().map(str::to_string)
+}
+
+/// Total size of the files under `dir`.
+async fn dir_size(dir: &Path) -> Result<u64, OpfsError> {
+    let mut total = 0;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in tokio_fs_ext::read_dir(&dir).await? {
+            let path = dir.join(entry?.file_name());
+            let metadata = tokio_fs_ext::metadata(&path).await?;
+            if metadata.is_dir() {
+                stack.push(path);
+            } else {
+                total += metadata.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    async fn write_extracted(dir: &Path, package_json: &str) {
+        tokio_fs_ext::create_dir_all(dir).await.unwrap();
+        tokio_fs_ext::write(dir.join("package.json"), package_json)
+            .await
+            .unwrap();
+        tokio_fs_ext::write(dir.join("index.js"), "module.exports = 1;")
+            .await
+            .unwrap();
+        tokio_fs_ext::write(resolved_marker_path(dir), b"")
+            .await
+            .unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_tgz_path_ignores_query_and_fragment() {
+        let store = Store::new(&Config::default());
+        let expected = PathBuf::from("/stores/lodash/-/lodash-4.17.21.tgz");
+        for url in [
+            "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+            "https://cdn.example.com/lodash/-/lodash-4.17.21.tgz?cache=1",
+            "https://cdn.example.com/lodash/-/lodash-4.17.21.tgz#sha256=abc",
+            "https://cdn.example.com/lodash/-/lodash-4.17.21.tgz?a=1&b=2#frag",
+            "lodash/-/lodash-4.17.21.tgz?cache=1",
+        ] {
+            assert_eq!(store.tgz_path("lodash", url), expected, "{url}");
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_with_retry_tries_at_least_once() {
+        let store = Store::new(&Config::default());
+        let url = "https://registry.npmjs.org/pkg/-/pkg-1.0.0.tgz";
+        let attempts = std::cell::Cell::new(0);
+        let unavailable = || {
+            attempts.set(attempts.get() + 1);
+            std::future::ready(Err::<(), _>(OpfsError::Http {
+                status: 503,
+                url: url.to_string(),
+            }))
+        };
+
+        // Zero attempts still tries once and returns that attempt's error
+        let err = store.with_retry(url, 0, unavailable).await.unwrap_err();
+        assert_eq!(attempts.get(), 1);
+        assert!(matches!(err, OpfsError::Http { status: 503, .. }));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_write_atomic() {
+        let dir = Path::new("/test_store_write_atomic");
+        let _ = tokio_fs_ext::remove_dir_all(dir).await;
+        tokio_fs_ext::create_dir_all(dir).await.unwrap();
+        let temp_files = || async {
+            tokio_fs_ext::read_dir(dir)
+                .await
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .filter(|name| name.contains(".tmp."))
+                .count()
+        };
+
+        let path = dir.join("file.txt");
+        write_atomic(&path, b"first").await.unwrap();
+        write_atomic(&path, b"second").await.unwrap();
+        assert_eq!(tokio_fs_ext::read(&path).await.unwrap(), b"second");
+        assert_eq!(temp_files().await, 0);
+
+        // Renaming over a directory fails: the temp file must not linger
+        let blocked = dir.join("blocked");
+        tokio_fs_ext::create_dir_all(&blocked).await.unwrap();
+        assert!(write_atomic(&blocked, b"data").await.is_err());
+        assert_eq!(temp_files().await, 0);
+
+        let _ = tokio_fs_ext::remove_dir_all(dir).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_verify_cached() {
+        let root = Path::new("/test_store_verify_cached");
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
+        let store = Store::new(&Config {
+            store_root: root.to_path_buf(),
+            ..Default::default()
+        });
+        let url = "https://registry.npmjs.org/pkg/-/pkg-1.0.0.tgz";
+        let tgz = b"not really a tarball";
+        let integrity = format!("sha512-{}", archive::sig_sha512_base64(tgz));
+        let verify = || store.verify_cached("pkg", url, Some(&integrity), None);
+
+        // Nothing cached yet
+        assert!(!verify().await.unwrap());
+
+        // Downloaded but never extracted
+        let tgz_path = store.tgz_path("pkg", url);
+        tokio_fs_ext::create_dir_all(tgz_path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio_fs_ext::write(&tgz_path, tgz).await.unwrap();
+        assert!(!verify().await.unwrap());
+
+        write_extracted(&tgz_path.with_extension(""), "{}").await;
+        assert!(verify().await.unwrap());
+        // Nothing to check the tgz against
+        assert!(store.verify_cached("pkg", url, None, None).await.unwrap());
+
+        // Truncated tgz
+        tokio_fs_ext::write(&tgz_path, &tgz[..4]).await.unwrap();
+        assert!(!verify().await.unwrap());
+
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_remove_package() {
+        let root = Path::new("/test_store_remove_package");
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
+        let store = Store::new(&Config {
+            store_root: root.to_path_buf(),
+            ..Default::default()
+        });
+        assert_eq!(store.remove_package("lodash").await.unwrap(), 0);
+
+        write_extracted(&root.join("lodash/-/lodash-4.17.20"), "{}").await;
+        write_extracted(&root.join("lodash/-/lodash-4.17.21"), "{}").await;
+        write_extracted(&root.join("react/-/react-18.0.0"), "{}").await;
+
+        // Two copies of package.json and index.js
+        let expected = 2 * (2 + "module.exports = 1;".len() as u64);
+        assert_eq!(store.remove_package("lodash").await.unwrap(), expected);
+        assert!(tokio_fs_ext::metadata(root.join("lodash")).await.is_err());
+        assert!(tokio_fs_ext::metadata(root.join("react")).await.is_ok());
+
+        // Names that would reach outside a package directory
+        for name in [
+            "",
+            ".",
+            "..",
+            "../react",
+            "/react",
+            "a/b",
+            "@scope",
+            "@scope/..",
+        ] {
+            assert!(store.remove_package(name).await.is_err(), "{name:?}");
+        }
+        assert!(tokio_fs_ext::metadata(root.join("react")).await.is_ok());
+        write_extracted(&root.join("@scope/pkg/-/pkg-1.0.0"), "{}").await;
+        assert!(store.remove_package("@scope/pkg").await.unwrap() > 0);
+
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_list_packages() {
+        let root = Path::new("/test_store_list");
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
+        let store = Store::new(&Config {
+            store_root: root.to_path_buf(),
+            ..Default::default()
+        });
+        assert!(store.list_packages().await.unwrap().is_empty());
+
+        let lodash = root.join("lodash/-/lodash-4.17.21");
+        let lodash_json = r#"{"name":"lodash","version":"4.17.21"}"#;
+        write_extracted(&lodash, lodash_json).await;
+        let scoped = root.join("@babel/core/-/core-7.0.0");
+        let scoped_json = r#"{"name":"@babel/core","version":"7.0.0"}"#;
+        write_extracted(&scoped, scoped_json).await;
+        // Incomplete extraction: no resolved marker
+        let partial = root.join("react/-/react-18.0.0");
+        tokio_fs_ext::create_dir_all(&partial).await.unwrap();
+
+        let packages = store.list_packages().await.unwrap();
+        assert_eq!(
+            packages,
+            vec![
+                InstalledPackage {
+                    name: "@babel/core".to_string(),
+                    version: "7.0.0".to_string(),
+                    store_path: scoped,
+                    size_bytes: scoped_json.len() as u64 + 19,
+                },
+                InstalledPackage {
+                    name: "lodash".to_string(),
+                    version: "4.17.21".to_string(),
+                    store_path: lodash,
+                    size_bytes: lodash_json.len() as u64 + 19,
+                },
+            ]
+        );
+
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_prune() {
+        let root = Path::new("/test_store_prune");
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
+        let store = Store::new(&Config {
+            store_root: root.to_path_buf(),
+            ..Default::default()
+        });
+
+        let url = |name: &str, file: &str| format!("https://registry.npmjs.org/{name}/-/{file}");
+        for (name, file) in [
+            ("lodash", "lodash-4.17.21.tgz"),
+            ("lodash", "lodash-4.17.20.tgz"),
+            ("@babel/core", "core-7.0.0.tgz"),
+            ("left-pad", "left-pad-1.3.0.tar.gz"),
+        ] {
+            let tgz_path = store.tgz_path(name, &url(name, file));
+            store.save(&tgz_path, b"tgz").await.unwrap();
+            write_extracted(&tgz_path.with_extension(""), "{}").await;
+        }
+        let keep: HashSet<PathBuf> = [
+            store.tgz_path("lodash", &url("lodash", "lodash-4.17.21.tgz")),
+            store.tgz_path("left-pad", &url("left-pad", "left-pad-1.3.0.tar.gz")),
+        ]
+        .into_iter()
+        .collect();
+        let expected = vec![
+            "@babel/core/-/core-7.0.0.tgz".to_string(),
+            "lodash/-/lodash-4.17.20.tgz".to_string(),
+        ];
+        // tgz + package.json + index.js per package
+        let freed = 2 * (3 + 2 + 19);
+
+        let report = store
+            .prune(&keep, PruneOptions { dry_run: true })
+            .await
+            .unwrap();
+        assert_eq!(report.removed_packages, expected);
+        assert_eq!(report.freed_bytes, freed);
+        assert!(
+            tokio_fs_ext::metadata(root.join("@babel/core"))
+                .await
+                .is_ok()
+        );
+
+        let report = store.prune(&keep, PruneOptions::default()).await.unwrap();
+        assert_eq!(report.removed_packages, expected);
+        assert_eq!(report.freed_bytes, freed);
+        assert!(
+            tokio_fs_ext::metadata(root.join("@babel/core"))
+                .await
+                .is_err()
+        );
+        assert!(
+            tokio_fs_ext::metadata(root.join("lodash/-/lodash-4.17.20"))
+                .await
+                .is_err()
+        );
+        assert!(
+            tokio_fs_ext::metadata(root.join("lodash/-/lodash-4.17.21"))
+                .await
+                .is_ok()
+        );
+        // `.tar.gz` extracts to `<name>.tar`, which stays with its tarball
+        for kept in [
+            "left-pad/-/left-pad-1.3.0.tar.gz",
+            "left-pad/-/left-pad-1.3.0.tar",
+            "left-pad/-/left-pad-1.3.0.tar._resolved",
+        ] {
+            assert!(tokio_fs_ext::metadata(root.join(kept)).await.is_ok());
+        }
+
+        let _ = tokio_fs_ext::remove_dir_all(root).await;
     }
 }
@@ -0,0 +1,543 @@
+//! `yarn.lock` parsing (classic v1 and berry) and conversion to
+//! [`PackageLock`].
+//!
+//! Like pnpm, yarn records a package graph keyed by `name@range`
+//! specifiers rather than a `node_modules` tree; the root project's direct
+//! dependencies come from its `package.json`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::package_lock::{LockPackage, PackageLock, ResolvedPackage, RootDependency};
+use crate::registry::{DEFAULT_REGISTRY, registry_tarball_url};
+
+/// Flavour of a `yarn.lock` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YarnLockFormat {
+    /// Yarn v1 custom format
+    Classic,
+    /// Yarn v2+ YAML format (has a `__metadata` entry)
+    Berry,
+}
+
+/// One resolved package, shared by every specifier that resolved to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct YarnEntry {
+    pub name: String,
+    pub version: String,
+    /// Tarball URL (classic only; berry lockfiles do not record it)
+    pub resolved: Option<String>,
+    /// Tarball SRI hash (classic only; berry's `checksum` hashes yarn's
+    /// cache zip, not the tarball, so it cannot verify a download)
+    pub integrity: Option<String>,
+    pub dependencies: HashMap<String, String>,
+    pub optional_dependencies: HashMap<String, String>,
+}
+
+/// Represents a complete `yarn.lock` file.
+#[derive(Debug, Clone)]
+pub struct YarnLock {
+    pub format: YarnLockFormat,
+    /// Entries keyed by `name@version`
+    pub entries: BTreeMap<String, YarnEntry>,
+    /// `name@range` specifier → key into `entries`
+    pub specifiers: HashMap<String, String>,
+}
+
+/// Berry entry, as deserialized from YAML.
+#[derive(Deserialize)]
+struct BerryEntry {
+    version: String,
+    resolution: Option<String>,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+    #[serde(default, rename = "optionalDependencies")]
+    optional_dependencies: HashMap<String, String>,
+}
+
+impl FromStr for YarnLock {
+    type Err = anyhow::Error;
+
+    /// Parse a `yarn.lock`, detecting classic or berry format.
+    fn from_str(s: &str) -> Result<Self> {
+        let is_berry = s.lines().any(|line| line.starts_with("__metadata:"));
+        if is_berry {
+            Self::parse_berry(s)
+        } else {
+            Self::parse_classic(s)
+        }
+    }
+}
+
+impl YarnLock {
+    fn new(format: YarnLockFormat) -> Self {
+        Self {
+            format,
+            entries: BTreeMap::new(),
+            specifiers: HashMap::new(),
+        }
+    }
+
+    /// Record `entry` under every specifier of its header.
+    fn insert(&mut self, specifiers: &[String], entry: YarnEntry) {
+        let key = format!("{}@{}", entry.name, entry.version);
+        for spec in specifiers {
+            self.specifiers.insert(spec.clone(), key.clone());
+        }
+        self.entries.entry(key).or_insert(entry);
+    }
+
+    fn parse_berry(s: &str) -> Result<Self> {
+        let raw: HashMap<String, serde_norway::Value> =
+            serde_norway::from_str(s).context("parse berry yarn.lock")?;
+        let mut lock = Self::new(YarnLockFormat::Berry);
+        for (header, value) in raw {
+            if header == "__metadata" {
+                continue;
+            }
+            let specifiers = split_header(&header);
+            let entry: BerryEntry = serde_norway::from_value(value)
+                .with_context(|| format!("invalid yarn.lock entry: {header}"))?;
+
+            // The header name is only the alias (`my-react@npm:react@^18`);
+            // the resolution names the actual package (`react@npm:18.2.0`).
+            let resolution = entry.resolution.as_deref().and_then(split_specifier);
+            let Some(name) = resolution.map(|(n, _)| n).or_else(|| {
+                specifiers
+                    .first()
+                    .and_then(|s| split_specifier(s))
+                    .map(|(n, _)| n)
+            }) else {
+                bail!("invalid yarn.lock entry: {header}");
+            };
+
+            let reference = resolution.map(|(_, reference)| reference);
+            if let Some(patch) = reference.filter(|r| r.starts_with("patch:")) {
+                tracing::warn!(
+                    "yarn.lock: patches are not applied, installing {name} unpatched ({patch})"
+                );
+            }
+
+            // `name@npm:1.2.3` resolutions come from the registry; others
+            // (`https:`, `workspace:`, …) keep their protocol as `resolved`.
+            let resolved = reference
+                .filter(|reference| {
+                    !reference.starts_with("npm:") && !reference.starts_with("patch:")
+                })
+                .map(|reference| match reference.split_once(':') {
+                    Some(("workspace" | "link" | "portal" | "file", path)) => {
+                        format!("file:{path}")
+                    }
+                    _ => reference.to_string(),
+                });
+
+            lock.insert(
+                &specifiers,
+                YarnEntry {
+                    name: name.to_string(),
+                    version: entry.version,
+                    resolved,
+                    // berry's `checksum` is not a tarball hash
+                    integrity: None,
+                    dependencies: entry.dependencies,
+                    optional_dependencies: entry.optional_dependencies,
+                },
+            );
+        }
+        Ok(lock)
+    }
+
+    fn parse_classic(s: &str) -> Result<Self> {
+        let mut lock = Self::new(YarnLockFormat::Classic);
+        let mut specifiers: Vec<String> = Vec::new();
+        let mut entry: Option<YarnEntry> = None;
+        let mut section: Option<String> = None;
+
+        for (index, line) in s.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - trimmed.len();
+            let line_no = index + 1;
+
+            match indent {
+                0 => {
+                    if let Some(done) = entry.take() {
+                        lock.insert(&specifiers, done);
+                    }
+                    let Some(header) = trimmed.strip_suffix(':') else {
+                        bail!("yarn.lock:{line_no}: expected entry header");
+                    };
+                    specifiers = split_header(header);
+                    let Some((name, _)) = specifiers.first().and_then(|s| split_specifier(s))
+                    else {
+                        bail!("yarn.lock:{line_no}: invalid specifier in {header}");
+                    };
+                    entry = Some(YarnEntry {
+                        name: name.to_string(),
+                        ..Default::default()
+                    });
+                    section = None;
+                }
+                2 => {
+                    let Some(current) = entry.as_mut() else {
+                        bail!("yarn.lock:{line_no}: field outside of an entry");
+                    };
+                    let (key, value) = split_field(trimmed);
+                    section = None;
+                    match (key.as_str(), value) {
+                        (key, None) => section = Some(key.to_string()),
+                        ("version", Some(v)) => current.version = v,
+                        ("resolved", Some(v)) => current.resolved = Some(v),
+                        ("integrity", Some(v)) => current.integrity = Some(v),
+                        _ => {}
+                    }
+                }
+                _ => {
+                    let Some(current) = entry.as_mut() else {
+                        bail!("yarn.lock:{line_no}: field outside of an entry");
+                    };
+                    let (key, value) = split_field(trimmed);
+                    let Some(value) = value else { continue };
+                    match section.as_deref() {
+                        Some("dependencies") => {
+                            current.dependencies.insert(key, value);
+                        }
+                        Some("optionalDependencies") => {
+                            current.optional_dependencies.insert(key, value);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        if let Some(done) = entry.take() {
+            lock.insert(&specifiers, done);
+        }
+        Ok(lock)
+    }
+
+    /// Look up the entry a `name@range` specifier resolved to.
+    pub fn get(&self, name: &str, range: &str) -> Option<&YarnEntry> {
+        let key = self
+            .specifiers
+            .get(&format!("{name}@{range}"))
+            // package.json ranges lack berry's `npm:` protocol prefix
+            .or_else(|| self.specifiers.get(&format!("{name}@npm:{range}")))?;
+        self.entries.get(key)
+    }
+
+    /// Convert to an npm-style [`PackageLock`].
+    ///
+    /// `package_json` is the root manifest: its `dependencies`,
+    /// `optionalDependencies` and `devDependencies` are the roots of the
+    /// tree. Tarball URLs missing from the lockfile are reconstructed
+    /// against [`DEFAULT_REGISTRY`].
+    pub fn to_package_lock(&self, package_json: &serde_json::Value) -> PackageLock {
+        let sections = [
+            ("dependencies", false, false),
+            ("optionalDependencies", false, true),
+            ("devDependencies", true, false),
+        ];
+        let mut roots = Vec::new();
+        for (field, dev, optional) in sections {
+            let Some(deps) = package_json.get(field).and_then(|v| v.as_object()) else {
+                continue;
+            };
+            let mut sorted: Vec<_> = deps
+                .iter()
+                .filter_map(|(name, range)| Some((name.as_str(), range.as_str()?)))
+                .collect();
+            sorted.sort();
+            roots.extend(sorted.into_iter().map(|(alias, reference)| RootDependency {
+                alias,
+                reference,
+                dev,
+                optional,
+            }));
+        }
+
+        PackageLock::from_dependency_graph(&roots, |alias, reference| {
+            let entry = self.get(alias, reference)?;
+            Some(self.to_resolved_package(alias, entry))
+        })
+    }
+
+    fn to_resolved_package<'a>(&self, alias: &str, entry: &'a YarnEntry) -> ResolvedPackage<'a> {
+        // Classic `resolved` URLs carry the sha1 shasum as fragment.
+        let (resolved, shasum) = match entry.resolved.as_deref() {
+            Some(url) => match url.split_once('#') {
+                Some((url, hash)) => (url.to_string(), Some(hash.to_string())),
+                None => (url.to_string(), None),
+            },
+            None => (
+                registry_tarball_url(DEFAULT_REGISTRY, &entry.name, &entry.version),
+                None,
+            ),
+        };
+
+        let mut dependencies: Vec<(&str, &str)> = entry
+            .dependencies
+            .iter()
+            .chain(entry.optional_dependencies.iter())
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        dependencies.sort();
+
+        ResolvedPackage {
+            key: format!("{}@{}", entry.name, entry.version),
+            package: LockPackage {
+                name: (entry.name != alias).then(|| entry.name.clone()),
+                version: Some(entry.version.clone()),
+                resolved: Some(resolved),
+                integrity: entry.integrity.clone(),
+                shasum,
+                dependencies: (!entry.dependencies.is_empty()).then(|| entry.dependencies.clone()),
+                optional_dependencies: (!entry.optional_dependencies.is_empty())
+                    .then(|| entry.optional_dependencies.clone()),
+                ..Default::default()
+            },
+            dependencies,
+        }
+    }
+}
+
+/// Split an entry header into its specifiers, e.g.
+/// `"a@^1.0.0", "a@^1.1.0"` → `["a@^1.0.0", "a@^1.1.0"]`.
+fn split_header(header: &str) -> Vec<String> {
+    header
+        .split(", ")
+        .map(|spec| spec.trim().trim_matches('"').to_string())
+        .filter(|spec| !spec.is_empty())
+        .collect()
+}
+
+/// Split `name@range` into name and range, handling scoped names.
+fn split_specifier(spec: &str) -> Option<(&str, &str)> {
+    let at = spec.get(1..)?.find('@')? + 1;
+    Some((&spec[..at], &spec[at + 1..]))
+}
+
+/// Split a classic field line into key and optional (unquoted) value:
+/// `version "1.0.0"` → (`version`, `1.0.0`), `dependencies:` →
+/// (`dependencies`, None).
+fn split_field(line: &str) -> (String, Option<String>) {
+    let unquote = |s: &str| s.trim().trim_matches('"').to_string();
+    let (key, rest) = if let Some(quoted) = line.strip_prefix('"') {
+        match quoted.find('"') {
+            Some(end) => (&quoted[..end], &quoted[end + 1..]),
+            None => (quoted, ""),
+        }
+    } else {
+        line.split_once(char::is_whitespace).unwrap_or((line, ""))
+    };
+    let key = key.trim_end_matches(':');
+    let rest = rest.trim();
+    if rest.is_empty() || rest == ":" {
+        (key.to_string(), None)
+    } else {
+        (key.to_string(), Some(unquote(rest)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_dedicated_worker);
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const CLASSIC: &str = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@types/node@^18.0.0", "@types/node@^18.1.0":
+  version "18.2.0"
+  resolved "https://registry.yarnpkg.com/@types/node/-/node-18.2.0.tgz#0123abcd"
+  integrity sha512-types
+  dependencies:
+    undici-types "~5.26.4"
+
+undici-types@~5.26.4:
+  version "5.26.5"
+  resolved "https://registry.yarnpkg.com/undici-types/-/undici-types-5.26.5.tgz"
+  integrity sha512-undici
+"#;
+
+    const BERRY: &str = r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 6
+  cacheKey: 8
+
+"@types/node@npm:^18.0.0, @types/node@npm:^18.1.0":
+  version: 18.2.0
+  resolution: "@types/node@npm:18.2.0"
+  dependencies:
+    undici-types: "npm:~5.26.4"
+  checksum: deadbeef
+  languageName: node
+  linkType: hard
+
+"undici-types@npm:~5.26.4":
+  version: 5.26.5
+  resolution: "undici-types@npm:5.26.5"
+  languageName: node
+  linkType: hard
+"#;
+
+    #[wasm_bindgen_test]
+    fn test_parse_classic() {
+        let lock: YarnLock = CLASSIC.parse().unwrap();
+        assert_eq!(lock.format, YarnLockFormat::Classic);
+        // Two constraints deduplicate to a single entry
+        assert_eq!(lock.entries.len(), 2);
+        assert_eq!(
+            lock.get("@types/node", "^18.0.0"),
+            lock.get("@types/node", "^18.1.0")
+        );
+
+        let node = lock.get("@types/node", "^18.0.0").unwrap();
+        assert_eq!(node.name, "@types/node");
+        assert_eq!(node.version, "18.2.0");
+        assert_eq!(node.integrity.as_deref(), Some("sha512-types"));
+        assert_eq!(node.dependencies["undici-types"], "~5.26.4");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_berry() {
+        let lock: YarnLock = BERRY.parse().unwrap();
+        assert_eq!(lock.format, YarnLockFormat::Berry);
+        assert_eq!(lock.entries.len(), 2);
+
+        // package.json ranges resolve without the `npm:` prefix
+        let node = lock.get("@types/node", "^18.1.0").unwrap();
+        assert_eq!(node.version, "18.2.0");
+        assert_eq!(node.resolved, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_berry_alias() {
+        let lock: YarnLock = r#"
+__metadata:
+  version: 6
+
+"my-react@npm:react@^18":
+  version: 18.2.0
+  resolution: "react@npm:18.2.0"
+  languageName: node
+  linkType: hard
+
+"resolve@patch:resolve@npm%3A^1.22.0#~builtin<compat/resolve>":
+  version: 1.22.1
+  resolution: "resolve@patch:resolve@npm%3A1.22.1#~builtin<compat/resolve>::version=1.22.1&hash=07638b"
+  languageName: node
+  linkType: hard
+"#
+        .parse()
+        .unwrap();
+
+        let react = lock.get("my-react", "npm:react@^18").unwrap();
+        assert_eq!(react.name, "react");
+        assert!(lock.entries.contains_key("react@18.2.0"));
+        // Patched packages fall back to the unpatched registry tarball
+        assert_eq!(lock.entries["resolve@1.22.1"].resolved, None);
+
+        let manifest = serde_json::json!({
+            "dependencies": { "my-react": "npm:react@^18" }
+        });
+        let lock = lock.to_package_lock(&manifest);
+        let aliased = &lock.packages["node_modules/my-react"];
+        assert_eq!(aliased.name.as_deref(), Some("react"));
+        assert_eq!(
+            aliased.resolved.as_deref(),
+            Some("https://registry.npmjs.org/react/-/react-18.2.0.tgz")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_dev_root_also_production_dependency() {
+        let lock: YarnLock = r#"
+a@^1.0.0:
+  version "1.0.0"
+  resolved "https://registry.yarnpkg.com/a/-/a-1.0.0.tgz"
+  dependencies:
+    b "^1.0.0"
+
+b@^1.0.0:
+  version "1.0.0"
+  resolved "https://registry.yarnpkg.com/b/-/b-1.0.0.tgz"
+  dependencies:
+    c "^1.0.0"
+
+c@^1.0.0:
+  version "1.0.0"
+  resolved "https://registry.yarnpkg.com/c/-/c-1.0.0.tgz"
+
+d@^1.0.0:
+  version "1.0.0"
+  resolved "https://registry.yarnpkg.com/d/-/d-1.0.0.tgz"
+"#
+        .parse()
+        .unwrap();
+        // `b` is a dev root, laid out before `a` requires it
+        let manifest = serde_json::json!({
+            "dependencies": { "a": "^1.0.0" },
+            "devDependencies": { "b": "^1.0.0", "d": "^1.0.0" }
+        });
+        let lock = lock.to_package_lock(&manifest);
+
+        for name in ["a", "b", "c"] {
+            assert!(
+                !lock.packages[&format!("node_modules/{name}")].is_dev(),
+                "{name}"
+            );
+        }
+        assert!(lock.packages["node_modules/d"].is_dev());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_yarn_to_package_lock() {
+        let manifest = serde_json::json!({
+            "dependencies": { "@types/node": "^18.0.0" }
+        });
+
+        for source in [CLASSIC, BERRY] {
+            let lock = source
+                .parse::<YarnLock>()
+                .unwrap()
+                .to_package_lock(&manifest);
+            assert_eq!(lock.packages.len(), 3);
+
+            let node = &lock.packages["node_modules/@types/node"];
+            assert_eq!(node.version.as_deref(), Some("18.2.0"));
+            let undici = &lock.packages["node_modules/undici-types"];
+            assert_eq!(undici.version.as_deref(), Some("5.26.5"));
+        }
+
+        let classic = CLASSIC
+            .parse::<YarnLock>()
+            .unwrap()
+            .to_package_lock(&manifest);
+        let node = &classic.packages["node_modules/@types/node"];
+        assert_eq!(
+            node.resolved.as_deref(),
+            Some("https://registry.yarnpkg.com/@types/node/-/node-18.2.0.tgz")
+        );
+        assert_eq!(node.shasum.as_deref(), Some("0123abcd"));
+
+        let berry = BERRY
+            .parse::<YarnLock>()
+            .unwrap()
+            .to_package_lock(&manifest);
+        assert_eq!(
+            berry.packages["node_modules/undici-types"]
+                .resolved
+                .as_deref(),
+            Some("https://registry.npmjs.org/undici-types/-/undici-types-5.26.5.tgz")
+        );
+    }
+}